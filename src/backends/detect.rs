@@ -0,0 +1,283 @@
+// Picks a backend for `backend = "auto"` from the session environment.
+// Checks run from most to least specific: the compositor's own IPC variables
+// (the same ones `daemon_lock::session_key` keys on), the desktop name, IPC
+// sockets under XDG_RUNTIME_DIR, running processes, and finally any Wayland
+// or X display, which get the generic Wayland or X11 backend.
+use crate::cli::BackendType;
+use crate::error::{Error, Result};
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Reads one environment variable; tests pass a fixed map instead of the real environment.
+type Lookup<'a> = &'a dyn Fn(&str) -> Option<OsString>;
+
+/// Resolves `BackendType::Auto` to a concrete backend; other values pass through.
+///
+/// # Errors
+///
+/// Returns [`Error::Backend`] listing every probe if no supported compositor was found.
+pub fn resolve(backend: BackendType) -> Result<BackendType> {
+    if backend != BackendType::Auto {
+        return Ok(backend);
+    }
+    detect(&|var| env::var_os(var), Path::new("/proc"))
+}
+
+/// Runs every probe against `var` and the process table under `proc`.
+fn detect(var: Lookup, proc: &Path) -> Result<BackendType> {
+    let mut checked = Vec::new();
+    let found = from_env(var, &mut checked)
+        .or_else(|| from_desktop(var, &mut checked))
+        .or_else(|| from_sockets(var, &mut checked))
+        .or_else(|| from_processes(proc, &mut checked))
+        .or_else(|| from_display(var, &mut checked));
+
+    if let Some((backend, reason)) = found {
+        log::info!("Detected {backend:?} backend ({reason})");
+        return Ok(backend);
+    }
+
+    Err(Error::Backend(anyhow::anyhow!(
+        "could not detect the compositor; checked:\n  - {}\nIs this running inside the graphical session? \
+         Set `backend` in the config or pass --backend.",
+        checked.join("\n  - ")
+    )))
+}
+
+type Found = Option<(BackendType, String)>;
+
+fn from_env(var: Lookup, checked: &mut Vec<String>) -> Found {
+    for (name, backend) in [
+        ("HYPRLAND_INSTANCE_SIGNATURE", BackendType::Hyprland),
+        ("NIRI_SOCKET", BackendType::Niri),
+        ("SWAYSOCK", BackendType::Sway),
+    ] {
+        if var(name).is_some_and(|v| !v.is_empty()) {
+            return Some((backend, format!("${name} is set")));
+        }
+        checked.push(format!("${name} (not set)"));
+    }
+    None
+}
+
+fn from_desktop(var: Lookup, checked: &mut Vec<String>) -> Found {
+    for name in ["XDG_CURRENT_DESKTOP", "XDG_SESSION_DESKTOP"] {
+        let Some(value) = var(name).and_then(|v| v.into_string().ok()) else {
+            checked.push(format!("${name} (not set)"));
+            continue;
+        };
+        // XDG_CURRENT_DESKTOP is a colon-separated list, e.g. "sway:wlroots"
+        for desktop in value.split(':') {
+            if let Some(backend) = backend_for_name(desktop) {
+                return Some((backend, format!("${name}={value}")));
+            }
+        }
+        checked.push(format!("${name}={value} (not recognized)"));
+    }
+    None
+}
+
+fn from_sockets(var: Lookup, checked: &mut Vec<String>) -> Found {
+    let Some(runtime) = var("XDG_RUNTIME_DIR").map(PathBuf::from) else {
+        checked.push("IPC sockets ($XDG_RUNTIME_DIR not set)".to_string());
+        return None;
+    };
+
+    let hypr = runtime.join("hypr");
+    let hypr_socket = read_dir(&hypr)
+        .into_iter()
+        .map(|dir| dir.join(".socket.sock"))
+        .find(|sock| sock.exists());
+    if let Some(sock) = hypr_socket {
+        return Some((BackendType::Hyprland, format!("found {}", sock.display())));
+    }
+    checked.push(format!("{}/*/.socket.sock (none)", hypr.display()));
+
+    let sockets = read_dir(&runtime);
+    for (prefix, backend) in [
+        ("niri.", BackendType::Niri),
+        ("sway-ipc.", BackendType::Sway),
+    ] {
+        let found = sockets.iter().find(|p| {
+            p.extension().is_some_and(|ext| ext == "sock")
+                && p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(prefix))
+        });
+        if let Some(sock) = found {
+            return Some((backend, format!("found {}", sock.display())));
+        }
+        checked.push(format!("{}/{prefix}*.sock (none)", runtime.display()));
+    }
+
+    None
+}
+
+fn from_processes(proc: &Path, checked: &mut Vec<String>) -> Found {
+    let found = read_dir(proc).into_iter().find_map(|dir| {
+        let comm = fs::read_to_string(dir.join("comm")).ok()?;
+        backend_for_name(comm.trim()).map(|b| (b, comm.trim().to_string()))
+    });
+    if let Some((backend, name)) = found {
+        return Some((backend, format!("found a running {name} process")));
+    }
+    checked.push(
+        "running Hyprland, niri, sway, gnome-shell or plasmashell process (none)".to_string(),
+    );
+    None
+}
+
+/// Any other Wayland session gets the generic backend; an X session gets X11.
+fn from_display(var: Lookup, checked: &mut Vec<String>) -> Found {
+    for (name, backend) in [
+        ("WAYLAND_DISPLAY", BackendType::Wayland),
+        ("DISPLAY", BackendType::X11),
+    ] {
+        match var(name).and_then(|v| v.into_string().ok()) {
+            Some(display) if !display.is_empty() => {
+                return Some((
+                    backend,
+                    format!("${name}={display}, no compositor-specific IPC"),
+                ));
+            }
+            _ => checked.push(format!("${name} (not set)")),
+        }
+    }
+    None
+}
+
+/// Maps a desktop or process name to the backend that drives it.
+fn backend_for_name(name: &str) -> Option<BackendType> {
+    match name.to_ascii_lowercase().as_str() {
+        "gnome" | "gnome-shell" => Some(BackendType::Gnome),
+        "hyprland" => Some(BackendType::Hyprland),
+        "kde" | "plasmashell" => Some(BackendType::Kde),
+        "niri" => Some(BackendType::Niri),
+        "sway" => Some(BackendType::Sway),
+        _ => None,
+    }
+}
+
+fn read_dir(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{StubDir, touch};
+    use std::collections::HashMap;
+
+    /// Runs detection with only `vars` set and the process table in `dir/proc`.
+    fn detect_with(dir: &StubDir, vars: &[(&str, &str)]) -> Result<BackendType> {
+        let env: HashMap<&str, OsString> = vars.iter().map(|&(k, v)| (k, v.into())).collect();
+        std::fs::create_dir_all(dir.path("proc")).expect("create proc");
+        detect(&|name| env.get(name).cloned(), &dir.path("proc"))
+    }
+
+    #[test]
+    fn picks_the_most_specific_session_variable() {
+        let table: &[(&[(&str, &str)], BackendType)] = &[
+            (&[("SWAYSOCK", "/run/sway.sock")], BackendType::Sway),
+            (&[("NIRI_SOCKET", "/run/niri.sock")], BackendType::Niri),
+            (
+                &[("HYPRLAND_INSTANCE_SIGNATURE", "abc")],
+                BackendType::Hyprland,
+            ),
+            // HYPRLAND_INSTANCE_SIGNATURE is checked before SWAYSOCK
+            (
+                &[
+                    ("SWAYSOCK", "/run/sway.sock"),
+                    ("HYPRLAND_INSTANCE_SIGNATURE", "abc"),
+                ],
+                BackendType::Hyprland,
+            ),
+            (
+                &[
+                    ("SWAYSOCK", "/run/sway.sock"),
+                    ("XDG_CURRENT_DESKTOP", "GNOME"),
+                ],
+                BackendType::Sway,
+            ),
+            (
+                &[("SWAYSOCK", ""), ("XDG_CURRENT_DESKTOP", "KDE")],
+                BackendType::Kde,
+            ),
+            (
+                &[("XDG_CURRENT_DESKTOP", "sway:wlroots")],
+                BackendType::Sway,
+            ),
+            (&[("XDG_SESSION_DESKTOP", "gnome")], BackendType::Gnome),
+            (
+                &[("XDG_CURRENT_DESKTOP", "GNOME"), ("DISPLAY", ":0")],
+                BackendType::Gnome,
+            ),
+            (
+                &[("WAYLAND_DISPLAY", "wayland-1"), ("DISPLAY", ":0")],
+                BackendType::Wayland,
+            ),
+            (
+                &[("XDG_CURRENT_DESKTOP", "XFCE"), ("DISPLAY", ":0")],
+                BackendType::X11,
+            ),
+        ];
+        for (vars, expected) in table {
+            let dir = StubDir::new();
+            let found = detect_with(&dir, vars).expect("detected");
+            assert_eq!(found, *expected, "{vars:?}");
+        }
+    }
+
+    #[test]
+    fn finds_ipc_sockets_and_processes() {
+        let dir = StubDir::new();
+        std::fs::create_dir_all(dir.path("run/hypr/abc")).expect("create hypr dir");
+        touch(&dir.path("run/hypr/abc/.socket.sock"));
+        let run = dir.path("run").display().to_string();
+        let found = detect_with(&dir, &[("XDG_RUNTIME_DIR", &run)]).expect("detected");
+        assert_eq!(found, BackendType::Hyprland);
+
+        let dir = StubDir::new();
+        std::fs::create_dir_all(dir.path("run")).expect("create run dir");
+        touch(&dir.path("run/niri.wayland-1.1234.sock"));
+        let run = dir.path("run").display().to_string();
+        let found = detect_with(&dir, &[("XDG_RUNTIME_DIR", &run)]).expect("detected");
+        assert_eq!(found, BackendType::Niri);
+
+        let dir = StubDir::new();
+        std::fs::create_dir_all(dir.path("proc/42")).expect("create process");
+        std::fs::write(dir.path("proc/42/comm"), "plasmashell\n").expect("write comm");
+        let found = detect_with(&dir, &[("DISPLAY", ":0")]).expect("detected");
+        assert_eq!(found, BackendType::Kde);
+    }
+
+    #[test]
+    fn lists_every_probe_without_a_session() {
+        let dir = StubDir::new();
+        let err = detect_with(&dir, &[("XDG_CURRENT_DESKTOP", "XFCE")]).expect_err("no session");
+        assert!(matches!(err, Error::Backend(_)), "{err:?}");
+        let message = err.to_string();
+        for probe in [
+            "could not detect the compositor",
+            "$SWAYSOCK (not set)",
+            "$XDG_CURRENT_DESKTOP=XFCE (not recognized)",
+            "$XDG_RUNTIME_DIR not set",
+            "$DISPLAY (not set)",
+            "--backend",
+        ] {
+            assert!(message.contains(probe), "missing {probe:?} in {message}");
+        }
+    }
+
+    #[test]
+    fn explicit_backends_skip_detection() {
+        assert_eq!(
+            resolve(BackendType::X11).expect("explicit"),
+            BackendType::X11
+        );
+    }
+}
@@ -0,0 +1,203 @@
+// Output filters from the `outputs` setting:
+// - Each entry is a glob (`*`, `?`, `[...]`) matched against an output's
+//   connector name, its make, model or serial, or the "make model serial"
+//   description Sway prints (e.g. "Dell Inc. DELL U2720Q 5KC0S3").
+// - Entries starting with `!` exclude outputs; the others include them. Without
+//   any include entry every output is included.
+// - Filtering never adds outputs and keeps the compositor's order, so an entry
+//   for a disconnected monitor is simply ignored.
+use crate::error::{Error, Result};
+use anyhow::anyhow;
+use glob::Pattern;
+
+/// What a compositor reports about one output.
+#[derive(Debug, Default, Clone)]
+pub struct OutputInfo {
+    pub name: String,
+    pub make: String,
+    pub model: String,
+    pub serial: String,
+}
+
+impl OutputInfo {
+    /// The output as "make model serial", skipping unknown parts.
+    fn description(&self) -> String {
+        [&self.make, &self.model, &self.serial]
+            .into_iter()
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty() && *s != "Unknown")
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Include/exclude patterns applied to the outputs a backend reports.
+#[derive(Debug, Default)]
+pub struct OutputFilter {
+    entries: Vec<String>,
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl OutputFilter {
+    /// Parses the `outputs` entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`] if an entry is not a valid glob.
+    pub fn new(entries: &[String]) -> Result<Self> {
+        let mut filter = Self {
+            entries: entries.to_vec(),
+            ..Self::default()
+        };
+        for entry in entries {
+            let (list, pattern) = match entry.strip_prefix('!') {
+                Some(rest) => (&mut filter.exclude, rest),
+                None => (&mut filter.include, entry.as_str()),
+            };
+            let pattern = Pattern::new(pattern.trim())
+                .map_err(|e| Error::Config(anyhow!("invalid output pattern {entry:?}: {e}")))?;
+            list.push(pattern);
+        }
+        Ok(filter)
+    }
+
+    /// Whether `output` passes the filter.
+    #[must_use]
+    pub fn matches(&self, output: &OutputInfo) -> bool {
+        let description = output.description();
+        let hit = |p: &Pattern| {
+            [
+                &output.name,
+                &description,
+                &output.make,
+                &output.model,
+                &output.serial,
+            ]
+            .into_iter()
+            .any(|s| !s.is_empty() && p.matches(s))
+        };
+        (self.include.is_empty() || self.include.iter().any(hit)) && !self.exclude.iter().any(hit)
+    }
+
+    /// Names of the outputs that pass, in their original order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`] if outputs were found but the filter rejects all of them.
+    pub fn apply(&self, outputs: Vec<OutputInfo>) -> Result<Vec<String>> {
+        let found: Vec<String> = outputs.iter().map(|o| o.name.clone()).collect();
+        let kept: Vec<String> = outputs
+            .into_iter()
+            .filter(|o| self.matches(o))
+            .map(|o| o.name)
+            .collect();
+        if kept.is_empty() && !found.is_empty() {
+            return Err(Error::Config(anyhow!(
+                "no output matches outputs = {:?} (found: {})",
+                self.entries,
+                found.join(", ")
+            )));
+        }
+        Ok(kept)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(name: &str, make: &str, model: &str, serial: &str) -> OutputInfo {
+        OutputInfo {
+            name: name.to_string(),
+            make: make.to_string(),
+            model: model.to_string(),
+            serial: serial.to_string(),
+        }
+    }
+
+    fn outputs() -> Vec<OutputInfo> {
+        vec![
+            output("eDP-1", "BOE", "0x095F", "Unknown"),
+            output("DP-1", "Dell Inc.", "DELL U2720Q", "5KC0S3"),
+            output("HDMI-A-1", "Goldstar Company Ltd", "LG TV", "0x01010101"),
+        ]
+    }
+
+    fn filter(entries: &[&str]) -> OutputFilter {
+        let entries: Vec<String> = entries.iter().map(ToString::to_string).collect();
+        OutputFilter::new(&entries).expect("valid filter")
+    }
+
+    #[test]
+    fn empty_filter_keeps_everything() {
+        assert_eq!(
+            filter(&[]).apply(outputs()).expect("outputs"),
+            ["eDP-1", "DP-1", "HDMI-A-1"]
+        );
+    }
+
+    #[test]
+    fn includes_by_name_glob_and_drops_missing_outputs() {
+        assert_eq!(
+            filter(&["HDMI-A-1", "DP-*", "DP-9"])
+                .apply(outputs())
+                .expect("outputs"),
+            ["DP-1", "HDMI-A-1"]
+        );
+    }
+
+    #[test]
+    fn matches_make_model_and_serial() {
+        assert_eq!(
+            filter(&["Dell Inc. DELL U2720Q 5KC0S3"])
+                .apply(outputs())
+                .expect("outputs"),
+            ["DP-1"]
+        );
+        assert_eq!(
+            filter(&["*LG TV*"]).apply(outputs()).expect("outputs"),
+            ["HDMI-A-1"]
+        );
+        assert_eq!(
+            filter(&["5KC0S3"]).apply(outputs()).expect("outputs"),
+            ["DP-1"]
+        );
+        // "Unknown" serials are left out of the description
+        assert_eq!(
+            filter(&["BOE 0x095F"]).apply(outputs()).expect("outputs"),
+            ["eDP-1"]
+        );
+    }
+
+    #[test]
+    fn excludes_win_over_includes() {
+        assert_eq!(
+            filter(&["!eDP-*"]).apply(outputs()).expect("outputs"),
+            ["DP-1", "HDMI-A-1"]
+        );
+        assert_eq!(
+            filter(&["*", "!Goldstar*"])
+                .apply(outputs())
+                .expect("outputs"),
+            ["eDP-1", "DP-1"]
+        );
+    }
+
+    #[test]
+    fn rejects_filters_that_match_nothing() {
+        let err = filter(&["DP-9"]).apply(outputs()).expect_err("should fail");
+        assert!(err.to_string().contains("found: eDP-1, DP-1"), "{err:#}");
+        assert!(
+            filter(&["DP-9"])
+                .apply(Vec::new())
+                .expect("empty")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(OutputFilter::new(&["DP-[".to_string()]).is_err());
+    }
+}
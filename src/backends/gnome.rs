@@ -0,0 +1,227 @@
+// GNOME backend and renderer in one:
+// - GNOME draws a single background across all monitors, so the backend
+//   reports one virtual output and the rotation picks one image for it.
+// - The image is set through `gsettings` on `org.gnome.desktop.background`,
+//   or written with `dconf` when gsettings is not installed.
+// - `picture-uri-dark` only exists since GNOME 42; failing to set it is not fatal.
+use crate::error::{Error, Result};
+use crate::overrides;
+use crate::traits::Backend;
+use anyhow::{Context, bail};
+use async_trait::async_trait;
+use std::fmt::Write;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use tokio::time::{Duration, timeout};
+
+const SCHEMA: &str = "org.gnome.desktop.background";
+const DCONF_DIR: &str = "/org/gnome/desktop/background";
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The single output GNOME exposes for wallpapers.
+pub const ALL_MONITORS: &str = "all";
+
+/// A combined backend and renderer for GNOME.
+pub struct GnomeBackend {
+    gsettings: PathBuf,
+    dconf: PathBuf,
+}
+
+impl Default for GnomeBackend {
+    fn default() -> Self {
+        Self {
+            gsettings: PathBuf::from("gsettings"),
+            dconf: PathBuf::from("dconf"),
+        }
+    }
+}
+
+/// Builds a `file://` URI, percent-encoding everything but unreserved characters and `/`.
+#[must_use]
+pub fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for &byte in path.as_os_str().as_encoded_bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            uri.push(char::from(byte));
+        } else {
+            let _ = write!(uri, "%{byte:02X}");
+        }
+    }
+    uri
+}
+
+/// Runs a short-lived tool, returning `Ok(None)` if it is not installed.
+async fn run(program: &Path, args: &[&str]) -> anyhow::Result<Option<std::process::Output>> {
+    let name = program.display();
+    match timeout(COMMAND_TIMEOUT, Command::new(program).args(args).output()).await {
+        Err(_) => bail!("{name}: timed out"),
+        Ok(Err(e)) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Ok(Err(e)) => Err(e).with_context(|| format!("{name}: failed to spawn")),
+        Ok(Ok(out)) if out.status.success() => Ok(Some(out)),
+        Ok(Ok(out)) => bail!(
+            "{name} {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&out.stderr).trim()
+        ),
+    }
+}
+
+impl GnomeBackend {
+    /// Uses the given `gsettings` and `dconf` executables.
+    #[cfg(test)]
+    #[must_use]
+    pub const fn with_tools(gsettings: PathBuf, dconf: PathBuf) -> Self {
+        Self { gsettings, dconf }
+    }
+
+    /// Sets one key, via gsettings if installed and dconf otherwise.
+    async fn set(&self, key: &str, value: &str) -> anyhow::Result<()> {
+        if run(&self.gsettings, &["set", SCHEMA, key, value])
+            .await?
+            .is_some()
+        {
+            return Ok(());
+        }
+
+        // dconf takes GVariant text, so strings need quoting
+        let path = format!("{DCONF_DIR}/{key}");
+        let quoted = format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'"));
+        match run(&self.dconf, &["write", &path, &quoted]).await? {
+            Some(_) => Ok(()),
+            None => bail!("neither gsettings nor dconf is installed"),
+        }
+    }
+
+    /// Sets the desktop background to the first assigned image.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Render`] if the image path is invalid or the settings
+    /// cannot be written.
+    pub async fn set_wallpaper(&self, assignments: &[(String, PathBuf)]) -> Result<()> {
+        let Some((_, img)) = assignments.first() else {
+            return Ok(());
+        };
+        self.set_background(img).await.map_err(Error::Render)
+    }
+
+    async fn set_background(&self, img: &Path) -> anyhow::Result<()> {
+        let img = img
+            .canonicalize()
+            .with_context(|| format!("gnome: invalid wallpaper {}", img.display()))?;
+        let uri = file_uri(&img);
+
+        self.set("picture-options", overrides::scale_mode(&img).gnome())
+            .await?;
+        self.set("picture-uri", &uri).await?;
+        if let Err(e) = self.set("picture-uri-dark", &uri).await {
+            log::debug!("gnome: not setting picture-uri-dark ({e:#})");
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Backend for GnomeBackend {
+    /// GNOME has one background for every monitor.
+    async fn get_active_monitors(&self) -> Result<Vec<String>> {
+        Ok(vec![ALL_MONITORS.to_string()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{StubDir, touch};
+
+    #[test]
+    fn encodes_file_uris() {
+        assert_eq!(
+            file_uri(Path::new("/home/me/My Walls/città #1.png")),
+            "file:///home/me/My%20Walls/citt%C3%A0%20%231.png"
+        );
+    }
+
+    #[tokio::test]
+    async fn sets_the_background_with_gsettings() {
+        let stubs = StubDir::new();
+        let gnome =
+            GnomeBackend::with_tools(stubs.command("gsettings", "", 0), stubs.missing("dconf"));
+        let img = stubs.path("wall.png");
+        touch(&img);
+        let uri = file_uri(&img.canonicalize().expect("canonicalize"));
+
+        gnome
+            .set_wallpaper(&[(ALL_MONITORS.to_string(), img)])
+            .await
+            .expect("set wallpaper");
+
+        let set = |key: &str, value: &str| {
+            vec![
+                "set".to_string(),
+                SCHEMA.to_string(),
+                key.to_string(),
+                value.to_string(),
+            ]
+        };
+        assert_eq!(
+            stubs.calls("gsettings"),
+            [
+                set("picture-options", "zoom"),
+                set("picture-uri", &uri),
+                set("picture-uri-dark", &uri),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_dconf() {
+        let stubs = StubDir::new();
+        let gnome =
+            GnomeBackend::with_tools(stubs.missing("gsettings"), stubs.command("dconf", "", 0));
+        let img = stubs.path("it's.png");
+        touch(&img);
+
+        gnome
+            .set_wallpaper(&[(ALL_MONITORS.to_string(), img.clone())])
+            .await
+            .expect("set wallpaper");
+
+        let calls = stubs.calls("dconf");
+        assert_eq!(calls.len(), 3);
+        assert_eq!(
+            calls[1],
+            [
+                "write".to_string(),
+                format!("{DCONF_DIR}/picture-uri"),
+                format!("'{}'", file_uri(&img.canonicalize().expect("canonicalize"))),
+            ]
+        );
+        assert_eq!(calls[0][2], "'zoom'");
+    }
+
+    #[tokio::test]
+    async fn surfaces_gsettings_failures() {
+        let stubs = StubDir::new();
+        let gnome =
+            GnomeBackend::with_tools(stubs.command("gsettings", "", 1), stubs.missing("dconf"));
+        let img = stubs.path("wall.png");
+        touch(&img);
+
+        let err = gnome
+            .set_wallpaper(&[(ALL_MONITORS.to_string(), img)])
+            .await
+            .expect_err("should fail");
+        assert!(err.to_string().contains("picture-options"), "{err:#}");
+    }
+
+    #[tokio::test]
+    async fn reports_one_virtual_output() {
+        let monitors = GnomeBackend::default()
+            .get_active_monitors()
+            .await
+            .expect("monitors");
+        assert_eq!(monitors, [ALL_MONITORS]);
+    }
+}
@@ -1,52 +1,476 @@
+// Native Hyprland backend:
+// - Requests go to `.socket.sock` (the same protocol `hyprctl` uses), one
+//   connection per request; Hyprland replies and closes.
+// - Events are read line by line from `.socket2.sock` as `EVENT>>DATA`.
+// - Every connect and read is bounded so oneshot never hangs on a dead socket.
+use crate::backends::filter::{OutputFilter, OutputInfo};
+use crate::error::{Error, Result};
+use crate::theme::Roles;
 use crate::traits::Backend;
-use anyhow::Context;
+use anyhow::{Context, anyhow, bail};
 use async_trait::async_trait;
 use serde::Deserialize;
-use tokio::process::Command;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::UnixStream;
+use tokio::net::unix::OwnedReadHalf;
+use tokio::time::{Duration, timeout};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(300);
+const REPLY_TIMEOUT: Duration = Duration::from_secs(1);
 
 /// A backend implementation for the Hyprland compositor.
 ///
-/// This backend uses the `hyprctl` command-line utility to communicate with
-/// the running Hyprland instance.
-pub struct HyprlandBackend;
+/// This backend talks to the running instance over its IPC sockets in
+/// `$XDG_RUNTIME_DIR/hypr/$HYPRLAND_INSTANCE_SIGNATURE/`.
+pub struct HyprlandBackend {
+    socket_dir: PathBuf,
+    outputs: OutputFilter,
+}
 
-/// Represents a subset of the JSON data returned by `hyprctl monitors`.
+/// Represents a subset of the JSON data returned by `j/monitors`.
 #[derive(Debug, Deserialize)]
 struct HyprMonitor {
     /// The name of the output (e.g., "eDP-1", "DP-2").
     name: String,
+    /// Whether this monitor currently has focus.
+    #[serde(default)]
+    focused: bool,
+    #[serde(default)]
+    make: String,
+    #[serde(default)]
+    model: String,
+    #[serde(default)]
+    serial: String,
+}
+
+impl From<HyprMonitor> for OutputInfo {
+    fn from(m: HyprMonitor) -> Self {
+        Self {
+            name: m.name,
+            make: m.make,
+            model: m.model,
+            serial: m.serial,
+        }
+    }
+}
+
+/// One line from the event socket, split at `>>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct HyprEvent {
+    pub name: String,
+    pub data: String,
+}
+
+/// A live connection to `.socket2.sock`.
+pub(crate) struct HyprEvents {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+}
+
+impl HyprEvents {
+    /// Waits for the next event; `None` once Hyprland closes the socket.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading from the socket fails.
+    pub(crate) async fn next(&mut self) -> anyhow::Result<Option<HyprEvent>> {
+        while let Some(line) = self
+            .lines
+            .next_line()
+            .await
+            .context("hyprland ipc: event read failed")?
+        {
+            if let Some((name, data)) = line.split_once(">>") {
+                return Ok(Some(HyprEvent {
+                    name: name.to_string(),
+                    data: data.to_string(),
+                }));
+            }
+            log::debug!("hyprland ipc: ignoring malformed event {line:?}");
+        }
+        Ok(None)
+    }
+}
+
+impl HyprlandBackend {
+    /// Locates the instance's socket directory from the environment.
+    ///
+    /// Uses `$HYPRLAND_INSTANCE_SIGNATURE` when set (checking the legacy
+    /// `/tmp/hypr` location too), otherwise the only instance under
+    /// `$XDG_RUNTIME_DIR/hypr`, so auto-detection via socket probing works
+    /// from outside the session environment.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no instance, or more than one, can be found.
+    pub fn from_env() -> Result<Self> {
+        Self::find_instance().map_err(Error::Backend)
+    }
+
+    fn find_instance() -> anyhow::Result<Self> {
+        let runtime = std::env::var_os("XDG_RUNTIME_DIR").map(|d| PathBuf::from(d).join("hypr"));
+
+        if let Some(sig) = std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE") {
+            let candidates = runtime
+                .iter()
+                .map(|r| r.join(&sig))
+                .chain(std::iter::once(Path::new("/tmp/hypr").join(&sig)));
+            for dir in candidates {
+                if dir.join(".socket.sock").exists() {
+                    return Ok(Self::with_socket_dir(dir));
+                }
+            }
+            bail!(
+                "no Hyprland socket for instance {}. Is Hyprland running?",
+                sig.to_string_lossy()
+            );
+        }
+
+        let runtime =
+            runtime.context("neither HYPRLAND_INSTANCE_SIGNATURE nor XDG_RUNTIME_DIR is set")?;
+        let mut instances: Vec<PathBuf> = std::fs::read_dir(&runtime)
+            .with_context(|| format!("failed to read {}. Is Hyprland running?", runtime.display()))?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|dir| dir.join(".socket.sock").exists())
+            .collect();
+        match instances.len() {
+            0 => bail!("no Hyprland instance found in {}", runtime.display()),
+            1 => Ok(Self::with_socket_dir(instances.remove(0))),
+            n => bail!(
+                "{n} Hyprland instances found in {}; set HYPRLAND_INSTANCE_SIGNATURE",
+                runtime.display()
+            ),
+        }
+    }
+
+    /// Uses the sockets in `dir` directly.
+    #[must_use]
+    pub fn with_socket_dir(dir: PathBuf) -> Self {
+        Self {
+            socket_dir: dir,
+            outputs: OutputFilter::default(),
+        }
+    }
+
+    /// Narrows the reported monitors down with the `outputs` filter.
+    #[must_use]
+    pub fn with_outputs(self, outputs: OutputFilter) -> Self {
+        Self { outputs, ..self }
+    }
+
+    async fn connect(&self, socket: &str) -> anyhow::Result<UnixStream> {
+        let path = self.socket_dir.join(socket);
+        timeout(CONNECT_TIMEOUT, UnixStream::connect(&path))
+            .await
+            .context("hyprland ipc: connect timed out")?
+            .with_context(|| format!("hyprland ipc: connect to {} failed", path.display()))
+    }
+
+    /// Sends one request to `.socket.sock` and returns the full reply.
+    async fn request(&self, command: &str) -> anyhow::Result<String> {
+        let mut stream = self.connect(".socket.sock").await?;
+        let exchange = async {
+            stream.write_all(command.as_bytes()).await?;
+            let mut reply = String::new();
+            stream.read_to_string(&mut reply).await?;
+            Ok::<_, std::io::Error>(reply)
+        };
+        timeout(REPLY_TIMEOUT, exchange)
+            .await
+            .with_context(|| format!("hyprland ipc: {command} timed out"))?
+            .with_context(|| format!("hyprland ipc: {command} failed"))
+    }
+
+    async fn query_monitors(&self) -> anyhow::Result<Vec<HyprMonitor>> {
+        let reply = self.request("j/monitors").await?;
+        serde_json::from_str(&reply).context("hyprland ipc: invalid monitors JSON")
+    }
+
+    /// Subscribes to the event socket (`.socket2.sock`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket cannot be reached.
+    pub(crate) async fn events(&self) -> anyhow::Result<HyprEvents> {
+        let (read, _write) = self.connect(".socket2.sock").await?.into_split();
+        Ok(HyprEvents {
+            lines: BufReader::new(read).lines(),
+        })
+    }
+}
+
+/// Builds the batch request that sets both border colors in one round trip.
+fn border_batch(roles: &Roles) -> String {
+    let active = roles.accent.hex();
+    let inactive = roles.bg.hex();
+    format!(
+        "[[BATCH]]keyword general:col.active_border rgb({});keyword general:col.inactive_border rgb({})",
+        active.trim_start_matches('#'),
+        inactive.trim_start_matches('#'),
+    )
 }
 
 #[async_trait]
 impl Backend for HyprlandBackend {
-    /// Retrieves a list of currently active monitor names via `hyprctl`.
+    /// Retrieves a list of currently active monitor names via `j/monitors`,
+    /// narrowed down by the `outputs` filter.
+    ///
+    /// # Errors
     ///
-    /// It executes `hyprctl -j monitors` to get a JSON representation of the
-    /// display layout. Because `hyprctl` may occasionally prepend non-JSON
-    /// log messages, this method includes a cleaning step to find the start
-    /// of the JSON array.
+    /// Returns [`Error::Backend`] if:
+    /// * The request socket cannot be reached or does not answer in time.
+    /// * The reply cannot be parsed into the expected format.
+    ///
+    /// Returns [`Error::Config`] if the filter rejects every monitor.
+    async fn get_active_monitors(&self) -> Result<Vec<String>> {
+        let monitors = self.query_monitors().await.map_err(Error::Backend)?;
+        self.outputs
+            .apply(monitors.into_iter().map(OutputInfo::from).collect())
+    }
+
+    /// Returns the monitor Hyprland marks as `focused` in `j/monitors`,
+    /// unless the `outputs` filter excludes it.
+    async fn get_focused_monitor(&self) -> Result<Option<String>> {
+        let monitors = self.query_monitors().await.map_err(Error::Backend)?;
+        Ok(monitors
+            .into_iter()
+            .find(|m| m.focused)
+            .map(OutputInfo::from)
+            .filter(|m| self.outputs.matches(m))
+            .map(|m| m.name))
+    }
+
+    /// Sets `general:col.active_border` and `general:col.inactive_border` via
+    /// a single batch request.
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// * The `hyprctl` binary is not found or fails to execute.
-    /// * The output from `hyprctl` is not valid UTF-8.
-    /// * The JSON cannot be parsed into the expected format.    
-    async fn get_active_monitors(&self) -> anyhow::Result<Vec<String>> {
-        let out = Command::new("hyprctl")
-            .args(["-j", "monitors"])
-            .output()
-            .await
-            .context("failed to execute `hyprctl`. Is Hyprland running?")?;
-
-        let raw = String::from_utf8(out.stdout)?;
-        // Simple JSON cleaning logic
-        // Find the first occurrence of '[' to skip any potential
-        // debug logging or headers emitted by `hyprctl`.
-        let start = raw.find('[').unwrap_or(0);
-        let clean_json = &raw[start..];
-
-        let monitors: Vec<HyprMonitor> = serde_json::from_str(clean_json)?;
-        Ok(monitors.into_iter().map(|m| m.name).collect())
+    /// Returns [`Error::Backend`] if the socket cannot be reached or a keyword is rejected.
+    async fn set_border_colors(&self, roles: &Roles) -> Result<()> {
+        let reply = self
+            .request(&border_batch(roles))
+            .await
+            .map_err(Error::Backend)?;
+        // A batch answers "ok" per command, separated by blank lines
+        if let Some(err) = reply.split("\n\n").map(str::trim).find(|r| *r != "ok") {
+            return Err(Error::Backend(anyhow!("hyprland ipc: batch failed: {err}")));
+        }
+        Ok(())
+    }
+
+    /// Waits for a `monitoradded` or `monitorremoved` event.
+    async fn wait_for_output_change(&self) -> Result<()> {
+        let mut events = self.events().await.map_err(Error::Backend)?;
+        while let Some(event) = events.next().await.map_err(Error::Backend)? {
+            if matches!(event.name.as_str(), "monitoradded" | "monitorremoved") {
+                log::debug!("hyprland: {} {}", event.name, event.data);
+                return Ok(());
+            }
+        }
+        Err(Error::Backend(anyhow!("hyprland ipc: event socket closed")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::StubDir;
+    use crate::theme::Rgb;
+    use tokio::net::UnixListener;
+    use tokio::sync::oneshot;
+
+    /// A throwaway instance directory, removed on drop.
+    struct FakeInstance {
+        dir: StubDir,
+    }
+
+    impl FakeInstance {
+        fn new() -> Self {
+            Self {
+                dir: StubDir::new(),
+            }
+        }
+
+        fn backend(&self) -> HyprlandBackend {
+            HyprlandBackend::with_socket_dir(self.dir.path(""))
+        }
+
+        /// Answers one request with `reply` and hands back what was asked.
+        fn serve_once(&self, reply: &'static str) -> oneshot::Receiver<String> {
+            let listener =
+                UnixListener::bind(self.dir.path(".socket.sock")).expect("bind request socket");
+            let (tx, rx) = oneshot::channel();
+            tokio::spawn(async move {
+                let (mut stream, _) = listener.accept().await.expect("accept");
+                let mut buf = vec![0; 4096];
+                let n = stream.read(&mut buf).await.expect("read request");
+                let _ = tx.send(String::from_utf8_lossy(&buf[..n]).into_owned());
+                stream
+                    .write_all(reply.as_bytes())
+                    .await
+                    .expect("write reply");
+            });
+            rx
+        }
+    }
+
+    fn roles(bg: &str, accent: &str) -> Roles {
+        let c = |hex| Rgb::from_hex(hex).expect("valid hex");
+        Roles {
+            bg: c(bg),
+            fg: c("#ffffff"),
+            accent: c(accent),
+            warn: c("#ff0000"),
+            ok: c("#00ff00"),
+        }
+    }
+
+    const MONITORS: &str = r#"[
+        {"id": 0, "name": "eDP-1", "make": "BOE", "model": "0x095F", "serial": "", "focused": false, "width": 1920},
+        {"id": 1, "name": "DP-2", "make": "Dell Inc.", "model": "DELL U2720Q", "serial": "5KC0S3", "focused": true, "width": 2560}
+    ]"#;
+
+    #[tokio::test]
+    async fn lists_monitors_over_the_request_socket() {
+        let hypr = FakeInstance::new();
+        let request = hypr.serve_once(MONITORS);
+
+        let monitors = hypr
+            .backend()
+            .get_active_monitors()
+            .await
+            .expect("monitors");
+        assert_eq!(monitors, ["eDP-1", "DP-2"]);
+        assert_eq!(request.await.expect("request"), "j/monitors");
+    }
+
+    #[tokio::test]
+    async fn filters_monitors_with_outputs() {
+        let hypr = FakeInstance::new();
+        let _request = hypr.serve_once(MONITORS);
+        let filter = OutputFilter::new(&["!Dell*".to_string()]).expect("filter");
+
+        let monitors = hypr
+            .backend()
+            .with_outputs(filter)
+            .get_active_monitors()
+            .await
+            .expect("monitors");
+        assert_eq!(monitors, ["eDP-1"]);
+    }
+
+    #[tokio::test]
+    async fn reports_the_focused_monitor() {
+        let hypr = FakeInstance::new();
+        let _request = hypr.serve_once(MONITORS);
+
+        let focused = hypr.backend().get_focused_monitor().await.expect("focused");
+        assert_eq!(focused.as_deref(), Some("DP-2"));
+    }
+
+    #[tokio::test]
+    async fn sends_border_colors_as_one_batch() {
+        let hypr = FakeInstance::new();
+        let request = hypr.serve_once("ok\n\nok");
+        hypr.backend()
+            .set_border_colors(&roles("#102030", "#aabbcc"))
+            .await
+            .expect("borders");
+        assert_eq!(
+            request.await.expect("request"),
+            "[[BATCH]]keyword general:col.active_border rgb(aabbcc);keyword general:col.inactive_border rgb(102030)"
+        );
+    }
+
+    #[tokio::test]
+    async fn surfaces_rejected_batch_commands() {
+        let hypr = FakeInstance::new();
+        let _request = hypr.serve_once("ok\n\nconfig option <general:col.nope> does not exist.");
+        let err = hypr
+            .backend()
+            .set_border_colors(&roles("#000000", "#000000"))
+            .await
+            .expect_err("should fail");
+        assert!(err.to_string().contains("does not exist"), "{err:#}");
+    }
+
+    #[tokio::test]
+    async fn times_out_when_the_compositor_never_answers() {
+        let hypr = FakeInstance::new();
+        let listener = UnixListener::bind(hypr.dir.path(".socket.sock")).expect("bind");
+        // Accept and hold the connection open without replying
+        let _server = tokio::spawn(async move {
+            let conn = listener.accept().await;
+            std::future::pending::<()>().await;
+            drop(conn);
+        });
+
+        let started = std::time::Instant::now();
+        let err = hypr
+            .backend()
+            .get_active_monitors()
+            .await
+            .expect_err("should fail");
+        assert!(err.to_string().contains("timed out"), "{err:#}");
+        assert!(started.elapsed() < REPLY_TIMEOUT * 2);
+    }
+
+    #[tokio::test]
+    async fn fails_fast_without_a_socket() {
+        let hypr = FakeInstance::new();
+        assert!(hypr.backend().get_active_monitors().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn reads_events_line_by_line() {
+        let hypr = FakeInstance::new();
+        let listener = UnixListener::bind(hypr.dir.path(".socket2.sock")).expect("bind events");
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.expect("accept");
+            stream
+                .write_all(b"workspace>>2\nnot an event\nmonitoraddedv2>>1,DP-3,desc\nmonitoradded>>DP-3\n")
+                .await
+                .expect("write events");
+        });
+
+        let backend = hypr.backend();
+        let mut events = backend.events().await.expect("subscribe");
+        let first = events.next().await.expect("read").expect("event");
+        assert_eq!(
+            first,
+            HyprEvent {
+                name: "workspace".to_string(),
+                data: "2".to_string()
+            }
+        );
+        // Malformed lines are skipped
+        let second = events.next().await.expect("read").expect("event");
+        assert_eq!(second.name, "monitoraddedv2");
+    }
+
+    #[tokio::test]
+    async fn output_change_skips_unrelated_events() {
+        let hypr = FakeInstance::new();
+        let listener = UnixListener::bind(hypr.dir.path(".socket2.sock")).expect("bind events");
+        let (done_tx, done_rx) = oneshot::channel::<()>();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.expect("accept");
+            stream
+                .write_all(b"activewindow>>kitty,~\nmonitorremoved>>HDMI-A-1\n")
+                .await
+                .expect("write events");
+            // Keep the socket open so only the event can end the wait
+            let _ = done_rx.await;
+        });
+
+        timeout(
+            Duration::from_secs(2),
+            hypr.backend().wait_for_output_change(),
+        )
+        .await
+        .expect("should not hang")
+        .expect("output change");
+        let _ = done_tx.send(());
     }
 }
@@ -0,0 +1,232 @@
+// KDE Plasma backend and renderer in one:
+// - Everything goes through `org.kde.PlasmaShell.evaluateScript`, called with
+//   `gdbus`; the reply is the text the script `print()`ed, as a GVariant tuple.
+// - Monitors are Plasma screen numbers ("0", "1", ...) taken from `desktops()`.
+//   Every desktop containment on a screen (one per activity) gets that
+//   screen's image.
+use crate::error::{Error, Result};
+use crate::overrides;
+use crate::traits::Backend;
+use anyhow::{Context, bail};
+use async_trait::async_trait;
+use std::fmt::Write;
+use std::path::PathBuf;
+use tokio::process::Command;
+use tokio::time::{Duration, timeout};
+
+/// Plasma can take a moment to load a new image plugin config.
+const SCRIPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+const LIST_SCREENS: &str = "print(desktops().filter(function (d) { return d.screen >= 0; })\
+    .map(function (d) { return d.screen; }).join('\\n'));";
+
+/// A combined backend and renderer for KDE Plasma.
+pub struct KdeBackend {
+    gdbus: PathBuf,
+}
+
+impl Default for KdeBackend {
+    fn default() -> Self {
+        Self {
+            gdbus: PathBuf::from("gdbus"),
+        }
+    }
+}
+
+/// Quotes `s` as a JavaScript string literal.
+fn js_string(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+/// Extracts the string from gdbus' `('...',)` reply and undoes its escaping.
+fn parse_gvariant_string(reply: &str) -> anyhow::Result<String> {
+    let inner = reply
+        .trim()
+        .strip_prefix('(')
+        .and_then(|s| s.strip_suffix(",)"))
+        .with_context(|| format!("unexpected gdbus reply: {reply}"))?;
+    let quote = inner.chars().next().filter(|c| *c == '\'' || *c == '"');
+    let body = quote
+        .and_then(|q| inner.strip_prefix(q)?.strip_suffix(q))
+        .with_context(|| format!("unexpected gdbus reply: {reply}"))?;
+
+    let mut out = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    Ok(out)
+}
+
+impl KdeBackend {
+    /// Uses the given `gdbus` executable.
+    #[cfg(test)]
+    #[must_use]
+    pub const fn with_gdbus(gdbus: PathBuf) -> Self {
+        Self { gdbus }
+    }
+
+    /// Runs a Plasma script and returns what it printed.
+    async fn evaluate(&self, script: &str) -> anyhow::Result<String> {
+        let call = Command::new(&self.gdbus)
+            .args([
+                "call",
+                "--session",
+                "--dest",
+                "org.kde.plasmashell",
+                "--object-path",
+                "/PlasmaShell",
+                "--method",
+                "org.kde.PlasmaShell.evaluateScript",
+                script,
+            ])
+            .output();
+        let out = timeout(SCRIPT_TIMEOUT, call)
+            .await
+            .context("plasma: evaluateScript timed out")?
+            .context("plasma: failed to spawn gdbus. Is it installed?")?;
+        if !out.status.success() {
+            bail!(
+                "plasma: evaluateScript failed: {}",
+                String::from_utf8_lossy(&out.stderr).trim()
+            );
+        }
+        parse_gvariant_string(&String::from_utf8_lossy(&out.stdout))
+    }
+
+    /// Sets each assigned screen's wallpaper in one script.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Render`] if an image path is invalid or Plasma rejects
+    /// the script.
+    pub async fn set_wallpaper(&self, assignments: &[(String, PathBuf)]) -> Result<()> {
+        if assignments.is_empty() {
+            return Ok(());
+        }
+        self.set_screens(assignments).await.map_err(Error::Render)
+    }
+
+    async fn set_screens(&self, assignments: &[(String, PathBuf)]) -> anyhow::Result<()> {
+        let mut wanted = String::new();
+        for (screen, img) in assignments {
+            let img = img
+                .canonicalize()
+                .with_context(|| format!("plasma: invalid wallpaper {}", img.display()))?;
+            let uri = super::gnome::file_uri(&img);
+            let mode = overrides::scale_mode(&img).plasma_fill_mode();
+            let _ = write!(
+                wanted,
+                "{}: [{}, {mode}], ",
+                js_string(screen),
+                js_string(&uri)
+            );
+        }
+
+        let script = format!(
+            "var wanted = {{{wanted}}};\n\
+             desktops().forEach(function (d) {{\n\
+             \x20   var w = wanted[String(d.screen)];\n\
+             \x20   if (!w) return;\n\
+             \x20   d.wallpaperPlugin = 'org.kde.image';\n\
+             \x20   d.currentConfigGroup = ['Wallpaper', 'org.kde.image', 'General'];\n\
+             \x20   d.writeConfig('Image', w[0]);\n\
+             \x20   d.writeConfig('FillMode', w[1]);\n\
+             }});"
+        );
+        self.evaluate(&script).await.map(drop)
+    }
+}
+
+#[async_trait]
+impl Backend for KdeBackend {
+    /// Lists the screens that have a desktop, in screen order.
+    async fn get_active_monitors(&self) -> Result<Vec<String>> {
+        let printed = self.evaluate(LIST_SCREENS).await.map_err(Error::Backend)?;
+        let mut screens: Vec<u32> = printed
+            .lines()
+            .filter_map(|l| l.trim().parse().ok())
+            .collect();
+        screens.sort_unstable();
+        screens.dedup();
+        Ok(screens.into_iter().map(|s| s.to_string()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{StubDir, touch};
+
+    #[test]
+    fn parses_gdbus_replies() {
+        assert_eq!(
+            parse_gvariant_string("('0\\n1',)\n").expect("parse"),
+            "0\n1"
+        );
+        assert_eq!(parse_gvariant_string("('',)").expect("parse"), "");
+        assert_eq!(parse_gvariant_string("(\"it's\",)").expect("parse"), "it's");
+        assert!(parse_gvariant_string("Error: nope").is_err());
+    }
+
+    #[tokio::test]
+    async fn lists_screens_from_desktops() {
+        let stubs = StubDir::new();
+        // Two activities on screen 0 and one on screen 1
+        let kde = KdeBackend::with_gdbus(stubs.command("gdbus", "('1\\n0\\n0',)\n", 0));
+
+        let monitors = kde.get_active_monitors().await.expect("monitors");
+        assert_eq!(monitors, ["0", "1"]);
+
+        let calls = stubs.calls("gdbus");
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0][..3], ["call", "--session", "--dest"]);
+        assert_eq!(calls[0][8], LIST_SCREENS);
+    }
+
+    #[tokio::test]
+    async fn sets_per_screen_wallpapers_in_one_script() {
+        let stubs = StubDir::new();
+        let kde = KdeBackend::with_gdbus(stubs.command("gdbus", "('',)\n", 0));
+        let (a, b) = (stubs.path("a.png"), stubs.path("b b.png"));
+        touch(&a);
+        touch(&b);
+
+        kde.set_wallpaper(&[("0".to_string(), a.clone()), ("1".to_string(), b.clone())])
+            .await
+            .expect("set wallpaper");
+
+        let calls = stubs.calls("gdbus");
+        assert_eq!(calls.len(), 1);
+        let script = calls[0][8..].join("\n");
+        let uri = |p: &PathBuf| super::super::gnome::file_uri(&p.canonicalize().expect("path"));
+        assert!(
+            script.contains(&format!("\"0\": [\"{}\", 2]", uri(&a))),
+            "{script}"
+        );
+        assert!(
+            script.contains(&format!("\"1\": [\"{}\", 2]", uri(&b))),
+            "{script}"
+        );
+        assert!(script.contains("d.writeConfig('Image', w[0]);"), "{script}");
+    }
+
+    #[tokio::test]
+    async fn surfaces_script_errors() {
+        let stubs = StubDir::new();
+        let kde = KdeBackend::with_gdbus(stubs.command("gdbus", "", 1));
+
+        let err = kde.get_active_monitors().await.expect_err("should fail");
+        assert!(err.to_string().contains("evaluateScript failed"), "{err:#}");
+    }
+}
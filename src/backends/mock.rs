@@ -0,0 +1,96 @@
+//! A scripted backend for exercising the daemon without a compositor.
+use crate::error::{Error, Result};
+use crate::theme::Roles;
+use crate::traits::Backend;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::sync::Notify;
+
+#[derive(Default)]
+struct State {
+    monitors: Mutex<Vec<String>>,
+    focused: Mutex<Option<String>>,
+    /// Errors handed out by the next monitor queries, before `monitors` is.
+    failures: Mutex<VecDeque<String>>,
+    queries: AtomicUsize,
+    borders: Mutex<Vec<Roles>>,
+    hotplug: Notify,
+}
+
+/// A backend whose monitors, failures and hotplug events are set by the test.
+///
+/// Clones share their state, so a test can keep one to drive the daemon that
+/// owns the other.
+#[derive(Clone, Default)]
+pub struct MockBackend {
+    state: Arc<State>,
+}
+
+fn lock<T>(m: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl MockBackend {
+    pub fn new(monitors: &[&str]) -> Self {
+        let mock = Self::default();
+        *lock(&mock.state.monitors) = monitors.iter().map(ToString::to_string).collect();
+        mock
+    }
+
+    /// Marks `monitor` as focused.
+    pub fn focus(&self, monitor: &str) {
+        *lock(&self.state.focused) = Some(monitor.to_string());
+    }
+
+    /// Makes the next `count` monitor queries fail.
+    pub fn fail_next(&self, count: usize) {
+        lock(&self.state.failures)
+            .extend((0..count).map(|i| format!("compositor unreachable ({i})")));
+    }
+
+    /// Replaces the monitor list and reports an output change.
+    pub fn plug(&self, monitors: &[&str]) {
+        *lock(&self.state.monitors) = monitors.iter().map(ToString::to_string).collect();
+        // `notify_one` keeps the event if the daemon is not waiting yet
+        self.state.hotplug.notify_one();
+    }
+
+    /// How many times the monitors were asked for.
+    pub fn queries(&self) -> usize {
+        self.state.queries.load(Ordering::SeqCst)
+    }
+
+    /// The border colors the daemon sent, oldest first.
+    pub fn borders(&self) -> Vec<Roles> {
+        lock(&self.state.borders).clone()
+    }
+}
+
+#[async_trait]
+impl Backend for MockBackend {
+    async fn get_active_monitors(&self) -> Result<Vec<String>> {
+        self.state.queries.fetch_add(1, Ordering::SeqCst);
+        let failure = lock(&self.state.failures).pop_front();
+        if let Some(e) = failure {
+            return Err(Error::Backend(anyhow!(e)));
+        }
+        Ok(lock(&self.state.monitors).clone())
+    }
+
+    async fn get_focused_monitor(&self) -> Result<Option<String>> {
+        Ok(lock(&self.state.focused).clone())
+    }
+
+    async fn set_border_colors(&self, roles: &Roles) -> Result<()> {
+        lock(&self.state.borders).push(*roles);
+        Ok(())
+    }
+
+    async fn wait_for_output_change(&self) -> Result<()> {
+        self.state.hotplug.notified().await;
+        Ok(())
+    }
+}
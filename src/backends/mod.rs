@@ -1,2 +1,13 @@
+pub mod detect;
+pub mod filter;
+pub mod gnome;
 pub mod hyprland;
+pub mod kde;
+pub mod niri;
 pub mod sway;
+pub mod wayland;
+pub mod x11;
+
+// Only the daemon tests drive it, and those need the `extract` feature
+#[cfg(all(test, feature = "extract"))]
+pub(crate) mod mock;
@@ -0,0 +1,382 @@
+// niri backend:
+// - Requests are JSON lines on `$NIRI_SOCKET`: we send `"Outputs"` and read
+//   back `{"Ok":{"Outputs":{...}}}` or `{"Err":"..."}`.
+// - `"EventStream"` turns a connection into a stream of one JSON event per line.
+// - niri has no event for outputs, so hotplug is noticed through
+//   `WorkspacesChanged` (workspaces move with their outputs) and confirmed by
+//   re-querying `Outputs`.
+// - Every connect and read is bounded, like the Sway and Hyprland backends.
+use crate::error::{Error, Result};
+use crate::traits::Backend;
+use anyhow::{Context, anyhow, bail};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::UnixStream;
+use tokio::net::unix::OwnedReadHalf;
+use tokio::time::{Duration, timeout};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(300);
+const REPLY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A backend implementation for the niri compositor, using its JSON IPC socket.
+pub struct NiriBackend {
+    socket: PathBuf,
+}
+
+/// The subset of an output record we need.
+#[derive(Debug, Deserialize)]
+struct NiriOutput {
+    name: String,
+    /// Position and size in the layout; `None` while the output is disabled.
+    #[serde(default)]
+    logical: Option<serde_json::Value>,
+}
+
+/// niri wraps every reply in a `Result`.
+#[derive(Debug, Deserialize)]
+enum Reply<T> {
+    Ok(T),
+    Err(String),
+}
+
+#[derive(Debug, Deserialize)]
+enum OutputsResponse {
+    Outputs(BTreeMap<String, NiriOutput>),
+}
+
+#[derive(Debug, Deserialize)]
+enum FocusedOutputResponse {
+    FocusedOutput(Option<NiriOutput>),
+}
+
+#[derive(Debug, Deserialize)]
+enum HandledResponse {
+    Handled,
+}
+
+/// A live `EventStream` connection.
+pub(crate) struct NiriEvents {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+}
+
+impl NiriEvents {
+    /// Waits for the next event and returns its name (e.g. `WorkspacesChanged`);
+    /// `None` once niri closes the socket.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading from the socket fails.
+    pub(crate) async fn next(&mut self) -> anyhow::Result<Option<String>> {
+        while let Some(line) = self
+            .lines
+            .next_line()
+            .await
+            .context("niri ipc: event read failed")?
+        {
+            // Events are externally tagged: {"WorkspacesChanged":{...}}
+            match serde_json::from_str::<BTreeMap<String, serde_json::Value>>(&line) {
+                Ok(event) if event.len() == 1 => return Ok(event.into_keys().next()),
+                _ => log::debug!("niri ipc: ignoring malformed event {line:?}"),
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl NiriBackend {
+    /// Uses the socket named by `$NIRI_SOCKET`, or the only `niri.*.sock` in
+    /// `$XDG_RUNTIME_DIR` when the variable is missing (e.g. after auto-detection
+    /// from outside the session environment).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Backend`] if no socket, or more than one, can be found.
+    pub fn from_env() -> Result<Self> {
+        Self::find_socket().map_err(Error::Backend)
+    }
+
+    fn find_socket() -> anyhow::Result<Self> {
+        if let Some(socket) = std::env::var_os("NIRI_SOCKET").filter(|s| !s.is_empty()) {
+            return Ok(Self::with_socket(PathBuf::from(socket)));
+        }
+
+        let runtime = std::env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .context("neither NIRI_SOCKET nor XDG_RUNTIME_DIR is set")?;
+        let mut sockets: Vec<PathBuf> = std::fs::read_dir(&runtime)
+            .with_context(|| format!("failed to read {}", runtime.display()))?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                p.extension().is_some_and(|ext| ext == "sock")
+                    && p.file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with("niri."))
+            })
+            .collect();
+        match sockets.len() {
+            0 => bail!(
+                "no niri socket found in {}. Is niri running?",
+                runtime.display()
+            ),
+            1 => Ok(Self::with_socket(sockets.remove(0))),
+            n => bail!(
+                "{n} niri sockets found in {}; set NIRI_SOCKET",
+                runtime.display()
+            ),
+        }
+    }
+
+    /// Uses the given socket directly.
+    #[must_use]
+    pub const fn with_socket(socket: PathBuf) -> Self {
+        Self { socket }
+    }
+
+    /// Sends one request and reads the first reply line.
+    async fn send(
+        &self,
+        request: &str,
+    ) -> anyhow::Result<(String, Lines<BufReader<OwnedReadHalf>>)> {
+        let stream = timeout(CONNECT_TIMEOUT, UnixStream::connect(&self.socket))
+            .await
+            .context("niri ipc: connect timed out")?
+            .with_context(|| format!("niri ipc: connect to {} failed", self.socket.display()))?;
+        let (read, mut write) = stream.into_split();
+        let mut lines = BufReader::new(read).lines();
+
+        let exchange = async {
+            write
+                .write_all(format!("\"{request}\"\n").as_bytes())
+                .await?;
+            lines.next_line().await
+        };
+        let line = timeout(REPLY_TIMEOUT, exchange)
+            .await
+            .with_context(|| format!("niri ipc: {request} timed out"))?
+            .with_context(|| format!("niri ipc: {request} failed"))?
+            .with_context(|| format!("niri ipc: {request}: connection closed without a reply"))?;
+        Ok((line, lines))
+    }
+
+    async fn request<T: DeserializeOwned>(&self, request: &str) -> anyhow::Result<T> {
+        let (line, _) = self.send(request).await?;
+        let reply: Reply<T> = serde_json::from_str(&line)
+            .with_context(|| format!("niri ipc: invalid {request} reply"))?;
+        match reply {
+            Reply::Ok(response) => Ok(response),
+            Reply::Err(e) => bail!("niri ipc: {request} rejected: {e}"),
+        }
+    }
+
+    /// Subscribes to niri's event stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket cannot be reached or refuses the stream.
+    pub(crate) async fn events(&self) -> anyhow::Result<NiriEvents> {
+        let (line, lines) = self.send("EventStream").await?;
+        match serde_json::from_str(&line).context("niri ipc: invalid EventStream reply")? {
+            Reply::Ok(HandledResponse::Handled) => Ok(NiriEvents { lines }),
+            Reply::Err(e) => bail!("niri ipc: EventStream rejected: {e}"),
+        }
+    }
+}
+
+#[async_trait]
+impl Backend for NiriBackend {
+    /// Lists enabled outputs (those with a logical position), sorted by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Backend`] if niri cannot be reached or rejects the request.
+    async fn get_active_monitors(&self) -> Result<Vec<String>> {
+        let OutputsResponse::Outputs(outputs) =
+            self.request("Outputs").await.map_err(Error::Backend)?;
+        Ok(outputs
+            .into_values()
+            .filter(|o| o.logical.is_some())
+            .map(|o| o.name)
+            .collect())
+    }
+
+    async fn get_focused_monitor(&self) -> Result<Option<String>> {
+        let FocusedOutputResponse::FocusedOutput(output) = self
+            .request("FocusedOutput")
+            .await
+            .map_err(Error::Backend)?;
+        Ok(output.map(|o| o.name))
+    }
+
+    /// Waits until the enabled outputs differ from when the call started.
+    async fn wait_for_output_change(&self) -> Result<()> {
+        let before = self.get_active_monitors().await?;
+        let mut events = self.events().await.map_err(Error::Backend)?;
+        while let Some(event) = events.next().await.map_err(Error::Backend)? {
+            if event == "WorkspacesChanged" && self.get_active_monitors().await? != before {
+                return Ok(());
+            }
+        }
+        Err(Error::Backend(anyhow!("niri ipc: event stream closed")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::StubDir;
+    use tokio::net::UnixListener;
+    use tokio::sync::mpsc;
+
+    /// A stand-in niri socket that answers each connection from a script.
+    struct FakeNiri {
+        dir: StubDir,
+        requests: mpsc::UnboundedReceiver<String>,
+    }
+
+    impl FakeNiri {
+        /// Serves `replies` in order, one per connection. Each reply is written
+        /// verbatim, so event streams can pass several lines at once.
+        fn serve(replies: Vec<&'static str>) -> Self {
+            let dir = StubDir::new();
+            let listener =
+                UnixListener::bind(dir.path("niri.sock")).expect("bind fake niri socket");
+
+            let (tx, requests) = mpsc::unbounded_channel();
+            tokio::spawn(async move {
+                let mut held = Vec::new();
+                for reply in replies {
+                    let (stream, _) = listener.accept().await.expect("accept");
+                    let (read, mut write) = stream.into_split();
+                    let mut lines = BufReader::new(read).lines();
+                    let request = lines.next_line().await.expect("read").unwrap_or_default();
+                    let _ = tx.send(request);
+                    write
+                        .write_all(reply.as_bytes())
+                        .await
+                        .expect("write reply");
+                    // Keep connections open so event streams don't end early
+                    held.push(write);
+                }
+                std::future::pending::<()>().await;
+                drop(held);
+            });
+
+            Self { dir, requests }
+        }
+
+        fn backend(&self) -> NiriBackend {
+            NiriBackend::with_socket(self.dir.path("niri.sock"))
+        }
+    }
+
+    const OUTPUTS: &str = concat!(
+        r#"{"Ok":{"Outputs":{"#,
+        r#""HDMI-A-1":{"name":"HDMI-A-1","make":"Dell","model":"U2720Q","logical":{"x":0,"y":0,"width":2560,"height":1440,"scale":1.5,"transform":"Normal"}},"#,
+        r#""eDP-1":{"name":"eDP-1","make":"BOE","model":"0x095F","logical":{"x":2560,"y":0,"width":1920,"height":1200,"scale":1.0,"transform":"Normal"}},"#,
+        r#""DP-3":{"name":"DP-3","make":"LG","model":"27GL850","logical":null}"#,
+        "}}}\n"
+    );
+
+    #[tokio::test]
+    async fn lists_enabled_outputs() {
+        let mut niri = FakeNiri::serve(vec![OUTPUTS]);
+
+        let monitors = niri.backend().get_active_monitors().await.expect("outputs");
+        assert_eq!(monitors, ["HDMI-A-1", "eDP-1"]);
+        assert_eq!(niri.requests.recv().await.as_deref(), Some("\"Outputs\""));
+    }
+
+    #[tokio::test]
+    async fn reports_the_focused_output() {
+        let niri = FakeNiri::serve(vec![
+            "{\"Ok\":{\"FocusedOutput\":{\"name\":\"eDP-1\",\"logical\":null}}}\n",
+            "{\"Ok\":{\"FocusedOutput\":null}}\n",
+        ]);
+        let backend = niri.backend();
+
+        let focused = backend.get_focused_monitor().await.expect("focused");
+        assert_eq!(focused.as_deref(), Some("eDP-1"));
+        let focused = backend.get_focused_monitor().await.expect("focused");
+        assert_eq!(focused, None);
+    }
+
+    #[tokio::test]
+    async fn surfaces_niri_errors() {
+        let niri = FakeNiri::serve(vec!["{\"Err\":\"unknown request\"}\n"]);
+
+        let err = niri
+            .backend()
+            .get_active_monitors()
+            .await
+            .expect_err("should fail");
+        assert!(err.to_string().contains("unknown request"), "{err:#}");
+    }
+
+    #[tokio::test]
+    async fn times_out_when_niri_never_answers() {
+        let niri = FakeNiri::serve(vec![""]);
+
+        let err = niri
+            .backend()
+            .get_active_monitors()
+            .await
+            .expect_err("should fail");
+        assert!(err.to_string().contains("timed out"), "{err:#}");
+    }
+
+    #[tokio::test]
+    async fn reads_the_event_stream() {
+        let mut niri = FakeNiri::serve(vec![concat!(
+            "{\"Ok\":\"Handled\"}\n",
+            "{\"WorkspacesChanged\":{\"workspaces\":[]}}\n",
+            "garbage\n",
+            "{\"WindowFocusChanged\":{\"id\":7}}\n",
+        )]);
+
+        let backend = niri.backend();
+        let mut events = backend.events().await.expect("subscribe");
+        assert_eq!(
+            niri.requests.recv().await.as_deref(),
+            Some("\"EventStream\"")
+        );
+        assert_eq!(
+            events.next().await.expect("read").as_deref(),
+            Some("WorkspacesChanged")
+        );
+        assert_eq!(
+            events.next().await.expect("read").as_deref(),
+            Some("WindowFocusChanged")
+        );
+    }
+
+    #[tokio::test]
+    async fn output_change_waits_for_a_different_output_set() {
+        const ONE_OUTPUT: &str =
+            "{\"Ok\":{\"Outputs\":{\"eDP-1\":{\"name\":\"eDP-1\",\"logical\":{}}}}}\n";
+        let niri = FakeNiri::serve(vec![
+            ONE_OUTPUT,
+            concat!(
+                "{\"Ok\":\"Handled\"}\n",
+                "{\"WorkspacesChanged\":{\"workspaces\":[]}}\n",
+                "{\"WorkspacesChanged\":{\"workspaces\":[]}}\n",
+            ),
+            // The first WorkspacesChanged is not an output change...
+            ONE_OUTPUT,
+            // ...the second one is
+            OUTPUTS,
+        ]);
+
+        timeout(
+            Duration::from_secs(2),
+            niri.backend().wait_for_output_change(),
+        )
+        .await
+        .expect("should not hang")
+        .expect("output change");
+    }
+}
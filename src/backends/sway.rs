@@ -1,30 +1,54 @@
 // Robust Sway backend:
-// - Uses swayipc_async first (pure Rust IPC).
+// - Uses swayipc_async first (pure Rust IPC) when built with the `swayipc` feature.
 // - If it errors or times out, falls back to `swaymsg -t get_outputs -r`.
 // - Never mutates SWAYSOCK env (avoids global races).
+use crate::backends::filter::{OutputFilter, OutputInfo};
+use crate::error::{Error, Result};
+use crate::theme::Roles;
 use crate::traits::Backend;
 use anyhow::{Context, bail};
 use async_trait::async_trait;
 use serde::Deserialize;
+#[cfg(feature = "swayipc")]
 use swayipc_async::Connection;
 use tokio::process::Command;
 use tokio::time::{Duration, timeout};
 
 /// Sway backend implementation.
 ///
-/// Can optionally accept a list of overridden outputs (from CLI args)
-/// to ignore what Sway reports and force specific monitors.
+/// Outputs Sway reports are narrowed down by the `outputs` filter.
 pub struct SwayBackend {
-    pub outputs_override: Vec<String>,
+    pub outputs: OutputFilter,
 }
 
+/// The subset of an output record both query paths agree on.
 #[derive(Debug, Deserialize)]
 struct SwaymsgOutput {
     name: String,
     active: bool,
+    #[serde(default)]
+    focused: bool,
+    #[serde(default)]
+    make: String,
+    #[serde(default)]
+    model: String,
+    #[serde(default)]
+    serial: String,
 }
 
-async fn get_outputs_via_swayipc() -> anyhow::Result<Vec<String>> {
+impl From<SwaymsgOutput> for OutputInfo {
+    fn from(o: SwaymsgOutput) -> Self {
+        Self {
+            name: o.name,
+            make: o.make,
+            model: o.model,
+            serial: o.serial,
+        }
+    }
+}
+
+#[cfg(feature = "swayipc")]
+async fn get_outputs_via_swayipc() -> anyhow::Result<Vec<SwaymsgOutput>> {
     // Keep these short so oneshot never "hangs for a while".
     let mut conn = timeout(Duration::from_millis(300), Connection::new())
         .await
@@ -38,12 +62,18 @@ async fn get_outputs_via_swayipc() -> anyhow::Result<Vec<String>> {
 
     Ok(outputs
         .into_iter()
-        .filter(|o| o.active)
-        .map(|o| o.name)
+        .map(|o| SwaymsgOutput {
+            name: o.name,
+            active: o.active,
+            focused: o.focused,
+            make: o.make,
+            model: o.model,
+            serial: o.serial,
+        })
         .collect())
 }
 
-async fn get_outputs_via_swaymsg() -> anyhow::Result<Vec<String>> {
+async fn get_outputs_via_swaymsg() -> anyhow::Result<Vec<SwaymsgOutput>> {
     let out = timeout(
         Duration::from_secs(1),
         Command::new("swaymsg")
@@ -61,37 +91,116 @@ async fn get_outputs_via_swaymsg() -> anyhow::Result<Vec<String>> {
         );
     }
 
-    let outputs: Vec<SwaymsgOutput> =
-        serde_json::from_slice(&out.stdout).context("swaymsg: invalid JSON")?;
+    serde_json::from_slice(&out.stdout).context("swaymsg: invalid JSON")
+}
 
-    Ok(outputs
-        .into_iter()
-        .filter(|o| o.active)
-        .map(|o| o.name)
-        .collect())
+/// Lists active outputs, trying pure Rust IPC first and `swaymsg` as a fallback.
+#[cfg(feature = "swayipc")]
+async fn get_active_outputs() -> anyhow::Result<Vec<SwaymsgOutput>> {
+    let active = |outputs: Vec<SwaymsgOutput>| -> Vec<SwaymsgOutput> {
+        outputs.into_iter().filter(|o| o.active).collect()
+    };
+
+    // First try: pure Rust IPC
+    match get_outputs_via_swayipc().await.map(active) {
+        Ok(outputs) if !outputs.is_empty() => return Ok(outputs),
+        Ok(_) => {
+            // empty list is suspicious; fall through to swaymsg
+            log::warn!("sway ipc returned 0 active outputs; falling back to swaymsg");
+        }
+        Err(e) => {
+            log::warn!("sway ipc failed ({e:#}); falling back to swaymsg");
+        }
+    }
+
+    // Fallback: swaymsg
+    get_outputs_via_swaymsg().await.map(active)
+}
+
+/// Lists active outputs through `swaymsg`.
+#[cfg(not(feature = "swayipc"))]
+async fn get_active_outputs() -> anyhow::Result<Vec<SwaymsgOutput>> {
+    let outputs = get_outputs_via_swaymsg().await?;
+    Ok(outputs.into_iter().filter(|o| o.active).collect())
+}
+
+/// Builds the `client.*` commands that color window borders from the theme roles.
+///
+/// Each command takes `<border> <background> <text> <indicator> <child_border>`.
+fn border_commands(roles: &Roles) -> String {
+    let accent = roles.accent.hex();
+    let bg = roles.bg.hex();
+    let fg = roles.fg.hex();
+    format!(
+        "client.focused {accent} {accent} {bg} {accent} {accent}; \
+         client.focused_inactive {bg} {bg} {fg} {bg} {bg}; \
+         client.unfocused {bg} {bg} {fg} {bg} {bg}"
+    )
+}
+
+#[cfg(feature = "swayipc")]
+async fn run_command_via_swayipc(cmd: &str) -> anyhow::Result<()> {
+    let mut conn = timeout(Duration::from_millis(300), Connection::new())
+        .await
+        .context("sway ipc: connect timed out")?
+        .context("sway ipc: connect failed")?;
+
+    let results = timeout(Duration::from_millis(300), conn.run_command(cmd))
+        .await
+        .context("sway ipc: run_command timed out")?
+        .context("sway ipc: run_command failed")?;
+
+    for result in results {
+        result.context("sway ipc: command rejected")?;
+    }
+    Ok(())
+}
+
+async fn run_command_via_swaymsg(cmd: &str) -> anyhow::Result<()> {
+    let out = timeout(
+        Duration::from_secs(1),
+        Command::new("swaymsg").arg(cmd).output(),
+    )
+    .await
+    .context("swaymsg: timed out")?
+    .context("swaymsg: failed to spawn")?;
+
+    if !out.status.success() {
+        bail!(
+            "swaymsg command failed: {}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    Ok(())
 }
 
 #[async_trait]
 impl Backend for SwayBackend {
-    async fn get_active_monitors(&self) -> anyhow::Result<Vec<String>> {
-        // If the user manually specified outputs in CLI, skip querying Sway
-        if !self.outputs_override.is_empty() {
-            return Ok(self.outputs_override.clone());
-        }
+    async fn get_active_monitors(&self) -> Result<Vec<String>> {
+        let outputs = get_active_outputs().await.map_err(Error::Backend)?;
+        self.outputs
+            .apply(outputs.into_iter().map(OutputInfo::from).collect())
+    }
 
-        // First try: pure Rust IPC
-        match get_outputs_via_swayipc().await {
-            Ok(names) if !names.is_empty() => return Ok(names),
-            Ok(_) => {
-                // empty list is suspicious; fall through to swaymsg
-                log::warn!("sway ipc returned 0 active outputs; falling back to swaymsg");
-            }
-            Err(e) => {
-                log::warn!("sway ipc failed ({e:#}); falling back to swaymsg");
-            }
-        }
+    async fn get_focused_monitor(&self) -> Result<Option<String>> {
+        let outputs = get_active_outputs().await.map_err(Error::Backend)?;
+        Ok(outputs
+            .into_iter()
+            .find(|o| o.focused)
+            .map(OutputInfo::from)
+            .filter(|o| self.outputs.matches(o))
+            .map(|o| o.name))
+    }
+
+    async fn set_border_colors(&self, roles: &Roles) -> Result<()> {
+        let cmd = border_commands(roles);
 
-        // Fallback: swaymsg
-        get_outputs_via_swaymsg().await
+        // Same strategy as monitor detection: IPC first, swaymsg as a fallback
+        #[cfg(feature = "swayipc")]
+        match run_command_via_swayipc(&cmd).await {
+            Ok(()) => return Ok(()),
+            Err(e) => log::warn!("sway ipc failed ({e:#}); falling back to swaymsg"),
+        }
+        run_command_via_swaymsg(&cmd).await.map_err(Error::Backend)
     }
 }
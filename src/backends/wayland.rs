@@ -0,0 +1,380 @@
+// Generic Wayland backend for compositors without an IPC randpaper knows
+// (river, labwc, Wayfire, ...):
+// - Prefers `zwlr_output_manager_v1`, which lists every head and whether it is
+//   enabled, the same view `wlr-randr` has.
+// - Falls back to `wl_output` names (v4+) or `zxdg_output_v1` names for older
+//   `wl_output` versions; outputs advertised this way are always enabled.
+// - The Wayland connection is blocking, so each query runs on a blocking
+//   thread under a timeout, like the IPC backends' connect and read limits.
+use crate::error::{Error, Result};
+use crate::traits::Backend;
+use anyhow::{Context, bail};
+use async_trait::async_trait;
+use tokio::time::{Duration, timeout};
+use wayland_client::globals::{GlobalListContents, registry_queue_init};
+use wayland_client::protocol::{wl_output, wl_registry};
+use wayland_client::{Connection, Dispatch, Proxy, QueueHandle, event_created_child};
+use wayland_protocols::xdg::xdg_output::zv1::client::{zxdg_output_manager_v1, zxdg_output_v1};
+use wayland_protocols_wlr::output_management::v1::client::{
+    zwlr_output_head_v1, zwlr_output_manager_v1, zwlr_output_mode_v1,
+};
+
+const QUERY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// First `wl_output` version with a `name` event.
+const WL_OUTPUT_NAME_SINCE: u32 = 4;
+
+/// A backend for any Wayland compositor, talking to `$WAYLAND_DISPLAY` directly.
+pub struct WaylandBackend;
+
+/// What the compositor has told us so far.
+#[derive(Default)]
+struct State {
+    heads: HeadList<zwlr_output_head_v1::ZwlrOutputHeadV1>,
+    /// Names from `wl_output` / `zxdg_output_v1`, indexed like the bound outputs.
+    output_names: Vec<Option<String>>,
+}
+
+/// Heads announced by `zwlr_output_manager_v1`, keyed by their proxy.
+struct HeadList<K> {
+    heads: Vec<Head<K>>,
+    /// Set once the manager has sent the full head list.
+    done: bool,
+}
+
+struct Head<K> {
+    key: K,
+    name: Option<String>,
+    enabled: bool,
+}
+
+impl<K> Default for HeadList<K> {
+    fn default() -> Self {
+        Self {
+            heads: Vec::new(),
+            done: false,
+        }
+    }
+}
+
+impl<K: PartialEq> HeadList<K> {
+    /// A new head starts out unnamed and disabled until its events arrive.
+    fn add(&mut self, key: K) {
+        self.heads.push(Head {
+            key,
+            name: None,
+            enabled: false,
+        });
+    }
+
+    fn get(&mut self, key: &K) -> Option<&mut Head<K>> {
+        self.heads.iter_mut().find(|h| h.key == *key)
+    }
+
+    fn set_name(&mut self, key: &K, name: String) {
+        if let Some(head) = self.get(key) {
+            head.name = Some(name);
+        }
+    }
+
+    fn set_enabled(&mut self, key: &K, enabled: bool) {
+        if let Some(head) = self.get(key) {
+            head.enabled = enabled;
+        }
+    }
+
+    /// The head was unplugged.
+    fn remove(&mut self, key: &K) {
+        self.heads.retain(|h| h.key != *key);
+    }
+
+    /// Names of the enabled heads, in the order the compositor announced them.
+    fn enabled_names(self) -> Vec<String> {
+        self.heads
+            .into_iter()
+            .filter(|h| h.enabled)
+            .filter_map(|h| h.name)
+            .collect()
+    }
+}
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for State {
+    fn event(
+        _: &mut Self,
+        _: &wl_registry::WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        // Outputs are listed once per query; later globals are irrelevant
+    }
+}
+
+impl Dispatch<zwlr_output_manager_v1::ZwlrOutputManagerV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        _: &zwlr_output_manager_v1::ZwlrOutputManagerV1,
+        event: zwlr_output_manager_v1::Event,
+        (): &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_output_manager_v1::Event::Head { head } => state.heads.add(head),
+            zwlr_output_manager_v1::Event::Done { .. } => state.heads.done = true,
+            _ => {}
+        }
+    }
+
+    event_created_child!(Self, zwlr_output_manager_v1::ZwlrOutputManagerV1, [
+        zwlr_output_manager_v1::EVT_HEAD_OPCODE => (zwlr_output_head_v1::ZwlrOutputHeadV1, ()),
+    ]);
+}
+
+impl Dispatch<zwlr_output_head_v1::ZwlrOutputHeadV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        proxy: &zwlr_output_head_v1::ZwlrOutputHeadV1,
+        event: zwlr_output_head_v1::Event,
+        (): &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_output_head_v1::Event::Name { name } => state.heads.set_name(proxy, name),
+            zwlr_output_head_v1::Event::Enabled { enabled } => {
+                state.heads.set_enabled(proxy, enabled != 0);
+            }
+            zwlr_output_head_v1::Event::Finished => state.heads.remove(proxy),
+            _ => {}
+        }
+    }
+
+    event_created_child!(Self, zwlr_output_head_v1::ZwlrOutputHeadV1, [
+        zwlr_output_head_v1::EVT_MODE_OPCODE => (zwlr_output_mode_v1::ZwlrOutputModeV1, ()),
+    ]);
+}
+
+impl Dispatch<zwlr_output_mode_v1::ZwlrOutputModeV1, ()> for State {
+    fn event(
+        _: &mut Self,
+        _: &zwlr_output_mode_v1::ZwlrOutputModeV1,
+        _: zwlr_output_mode_v1::Event,
+        (): &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<wl_output::WlOutput, usize> for State {
+    fn event(
+        state: &mut Self,
+        _: &wl_output::WlOutput,
+        event: wl_output::Event,
+        index: &usize,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let wl_output::Event::Name { name } = event {
+            state.output_names[*index] = Some(name);
+        }
+    }
+}
+
+impl Dispatch<zxdg_output_manager_v1::ZxdgOutputManagerV1, ()> for State {
+    fn event(
+        _: &mut Self,
+        _: &zxdg_output_manager_v1::ZxdgOutputManagerV1,
+        _: zxdg_output_manager_v1::Event,
+        (): &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<zxdg_output_v1::ZxdgOutputV1, usize> for State {
+    fn event(
+        state: &mut Self,
+        _: &zxdg_output_v1::ZxdgOutputV1,
+        event: zxdg_output_v1::Event,
+        index: &usize,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        // Only fill in what wl_output v4 didn't already name
+        if let zxdg_output_v1::Event::Name { name } = event {
+            state.output_names[*index].get_or_insert(name);
+        }
+    }
+}
+
+/// Checks the names collected from `wl_output` / `zxdg_output_v1`.
+fn fallback_names(names: Vec<Option<String>>) -> anyhow::Result<Vec<String>> {
+    if names.is_empty() {
+        bail!("wayland: the compositor advertises neither zwlr_output_manager_v1 nor wl_output");
+    }
+    let expected = names.len();
+    let names: Vec<String> = names.into_iter().flatten().collect();
+    if names.len() < expected {
+        bail!(
+            "wayland: the compositor does not name its outputs (needs wl_output v4 or xdg-output v2)"
+        );
+    }
+    Ok(names)
+}
+
+/// Connects to `$WAYLAND_DISPLAY` and lists the names of enabled outputs.
+fn list_outputs() -> anyhow::Result<Vec<String>> {
+    let conn = Connection::connect_to_env()
+        .context("wayland: failed to connect. Is WAYLAND_DISPLAY set?")?;
+    let (globals, mut queue) =
+        registry_queue_init::<State>(&conn).context("wayland: failed to list globals")?;
+    let qh = queue.handle();
+    let mut state = State::default();
+
+    if let Ok(manager) =
+        globals.bind::<zwlr_output_manager_v1::ZwlrOutputManagerV1, _, _>(&qh, 1..=4, ())
+    {
+        while !state.heads.done {
+            queue
+                .blocking_dispatch(&mut state)
+                .context("wayland: output manager dispatch failed")?;
+        }
+        manager.stop();
+        return Ok(state.heads.enabled_names());
+    }
+
+    log::debug!("wayland: zwlr_output_manager_v1 unavailable; using wl_output");
+    let xdg_manager = globals
+        .bind::<zxdg_output_manager_v1::ZxdgOutputManagerV1, _, _>(&qh, 2..=3, ())
+        .ok();
+    let outputs = globals
+        .contents()
+        .clone_list()
+        .into_iter()
+        .filter(|g| g.interface == wl_output::WlOutput::interface().name)
+        .enumerate()
+        .map(|(index, g)| {
+            let version = g.version.min(WL_OUTPUT_NAME_SINCE);
+            let output = globals
+                .registry()
+                .bind::<wl_output::WlOutput, _, _>(g.name, version, &qh, index);
+            if version < WL_OUTPUT_NAME_SINCE
+                && let Some(manager) = &xdg_manager
+            {
+                manager.get_xdg_output(&output, &qh, index);
+            }
+            output
+        })
+        .count();
+    state.output_names = vec![None; outputs];
+    queue
+        .roundtrip(&mut state)
+        .context("wayland: wl_output roundtrip failed")?;
+
+    fallback_names(state.output_names)
+}
+
+/// Runs a blocking `query` off the runtime, giving up after `limit`.
+async fn query_outputs(
+    query: impl FnOnce() -> anyhow::Result<Vec<String>> + Send + 'static,
+    limit: Duration,
+) -> anyhow::Result<Vec<String>> {
+    timeout(limit, tokio::task::spawn_blocking(query))
+        .await
+        .context("wayland: output query timed out")?
+        .context("wayland: output query panicked")?
+}
+
+#[async_trait]
+impl Backend for WaylandBackend {
+    /// Lists enabled outputs via wlr-output-management, or `wl_output` names.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the display cannot be reached, does not answer in
+    /// time, or does not expose output names.
+    async fn get_active_monitors(&self) -> Result<Vec<String>> {
+        query_outputs(list_outputs, QUERY_TIMEOUT)
+            .await
+            .map_err(Error::Backend)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_enabled_heads_in_order() {
+        let mut heads = HeadList::default();
+        for id in 1..=4 {
+            heads.add(id);
+        }
+        heads.set_name(&1, "eDP-1".to_string());
+        heads.set_name(&2, "DP-1".to_string());
+        heads.set_name(&3, "HDMI-A-1".to_string());
+        heads.set_name(&4, "DP-2".to_string());
+        heads.set_enabled(&2, true);
+        heads.set_enabled(&1, true);
+        heads.set_enabled(&4, true);
+        heads.set_enabled(&4, false);
+        // Events for a head that was never announced are ignored
+        heads.set_enabled(&9, true);
+
+        assert_eq!(heads.enabled_names(), ["eDP-1", "DP-1"]);
+    }
+
+    #[test]
+    fn drops_unnamed_and_unplugged_heads() {
+        let mut heads = HeadList::default();
+        heads.add("a");
+        heads.add("b");
+        heads.add("c");
+        heads.set_enabled(&"a", true);
+        heads.set_enabled(&"b", true);
+        heads.set_enabled(&"c", true);
+        heads.set_name(&"b", "DP-1".to_string());
+        heads.set_name(&"c", "DP-2".to_string());
+        heads.remove(&"c");
+
+        assert!(!heads.done);
+        assert_eq!(heads.enabled_names(), ["DP-1"]);
+    }
+
+    #[test]
+    fn requires_named_outputs_when_falling_back() {
+        let names = fallback_names(vec![Some("DP-1".into()), Some("HDMI-A-1".into())]);
+        assert_eq!(names.expect("names"), ["DP-1", "HDMI-A-1"]);
+
+        let err = fallback_names(vec![Some("DP-1".into()), None]).expect_err("unnamed output");
+        assert!(err.to_string().contains("does not name"), "{err:#}");
+
+        let err = fallback_names(Vec::new()).expect_err("no output globals");
+        assert!(
+            err.to_string()
+                .contains("neither zwlr_output_manager_v1 nor wl_output"),
+            "{err:#}"
+        );
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_a_compositor_that_never_answers() {
+        let names = query_outputs(|| Ok(vec!["DP-1".to_string()]), QUERY_TIMEOUT)
+            .await
+            .expect("names");
+        assert_eq!(names, ["DP-1"]);
+
+        let hang = || {
+            std::thread::sleep(Duration::from_millis(500));
+            Ok(Vec::new())
+        };
+        let err = query_outputs(hang, Duration::from_millis(20))
+            .await
+            .expect_err("timed out");
+        assert!(err.to_string().contains("timed out"), "{err:#}");
+    }
+}
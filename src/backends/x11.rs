@@ -0,0 +1,145 @@
+// X11 backend:
+// - Monitors come from `xrandr --listactivemonitors`, which reports RandR 1.5
+//   monitors (so a tiled or virtual monitor counts once):
+//       Monitors: 2
+//        0: +*DP-1 2560/597x1440/336+0+0  DP-1
+//        1: +HDMI-1 1920/527x1080/296+2560+0  HDMI-1
+//   Each monitor is reported by the first RandR output after its geometry,
+//   since that is the name `xwallpaper --output` matches. A virtual monitor
+//   without an output (`xrandr --setmonitor NAME ... none`) keeps its monitor
+//   name; only feh, which assigns images by position, can paint it.
+// - Wallpapers are drawn by the `feh` or `xwallpaper` renderers.
+use crate::error::{Error, Result};
+use crate::traits::Backend;
+use anyhow::{Context, bail};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use tokio::process::Command;
+use tokio::time::{Duration, timeout};
+
+const XRANDR_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A backend for X11 window managers (i3, bspwm, ...), using `xrandr`.
+pub struct X11Backend {
+    xrandr: PathBuf,
+}
+
+impl Default for X11Backend {
+    fn default() -> Self {
+        Self {
+            xrandr: PathBuf::from("xrandr"),
+        }
+    }
+}
+
+/// One name per monitor from `xrandr --listactivemonitors`, in the order
+/// `xrandr` lists them: its first output, or the monitor name if it has none.
+fn parse_monitors(out: &str) -> Vec<String> {
+    out.lines()
+        .filter_map(|line| {
+            let (index, rest) = line.trim().split_once(':')?;
+            index.parse::<u32>().ok()?;
+            let mut fields = rest.split_whitespace();
+            let monitor = fields.next()?.trim_start_matches(['+', '*']);
+            // Skip the geometry; the outputs follow it
+            let output = fields.nth(1).unwrap_or(monitor);
+            Some(output.to_string())
+        })
+        .collect()
+}
+
+impl X11Backend {
+    /// Uses the given `xrandr` executable.
+    #[cfg(test)]
+    #[must_use]
+    pub const fn with_xrandr(xrandr: PathBuf) -> Self {
+        Self { xrandr }
+    }
+
+    async fn list_monitors(&self) -> anyhow::Result<Vec<String>> {
+        let out = timeout(
+            XRANDR_TIMEOUT,
+            Command::new(&self.xrandr)
+                .arg("--listactivemonitors")
+                .output(),
+        )
+        .await
+        .context("xrandr: timed out")?
+        .context("xrandr: failed to spawn. Is it installed?")?;
+
+        if !out.status.success() {
+            bail!(
+                "xrandr --listactivemonitors failed: {}",
+                String::from_utf8_lossy(&out.stderr).trim()
+            );
+        }
+        Ok(parse_monitors(&String::from_utf8_lossy(&out.stdout)))
+    }
+}
+
+#[async_trait]
+impl Backend for X11Backend {
+    /// Lists active monitors from `xrandr` in listing order, which is also the
+    /// Xinerama order `feh` assigns images in.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `xrandr` cannot be run or cannot reach `$DISPLAY`.
+    async fn get_active_monitors(&self) -> Result<Vec<String>> {
+        self.list_monitors().await.map_err(Error::Backend)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::StubDir;
+
+    const LISTING: &str = "Monitors: 3\n \
+        0: +HDMI-1 1920/527x1080/296+0+0  HDMI-1\n \
+        1: +*DP-1 2560/597x1440/336+1920+0  DP-1\n \
+        2: VIRTUAL-LEFT 1280/338x1440/381+4480+0  DP-2\n";
+
+    #[test]
+    fn parses_listactivemonitors() {
+        assert_eq!(parse_monitors(LISTING), ["HDMI-1", "DP-1", "DP-2"]);
+        assert!(parse_monitors("Monitors: 0\n").is_empty());
+    }
+
+    #[test]
+    fn keeps_virtual_monitors_without_an_output() {
+        let listing = "Monitors: 2\n \
+            0: +*DP-1 1280/338x1440/381+0+0  DP-1\n \
+            1: SPLIT-RIGHT 1280/338x1440/381+1280+0\n";
+        assert_eq!(parse_monitors(listing), ["DP-1", "SPLIT-RIGHT"]);
+    }
+
+    #[tokio::test]
+    async fn lists_monitors_with_xrandr() {
+        let stubs = StubDir::new();
+        let x11 = X11Backend::with_xrandr(stubs.command("xrandr", LISTING, 0));
+
+        let monitors = x11.get_active_monitors().await.expect("monitors");
+        assert_eq!(monitors, ["HDMI-1", "DP-1", "DP-2"]);
+        assert_eq!(stubs.calls("xrandr"), [["--listactivemonitors"]]);
+    }
+
+    #[tokio::test]
+    async fn surfaces_xrandr_failures() {
+        let stubs = StubDir::new();
+        let x11 = X11Backend::with_xrandr(stubs.command("xrandr", "", 1));
+
+        assert!(x11.get_active_monitors().await.is_err());
+    }
+
+    /// Run under a headless server: `xvfb-run -s "-screen 0 1280x720x24" cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore = "needs an X server and xrandr"]
+    async fn lists_monitors_on_a_real_server() {
+        let monitors = X11Backend::default()
+            .get_active_monitors()
+            .await
+            .expect("monitors");
+        assert!(!monitors.is_empty());
+    }
+}
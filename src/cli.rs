@@ -1,9 +1,11 @@
-use clap::{Parser, ValueEnum};
+use crate::error::{Error, Result};
+use clap::{Parser, Subcommand, ValueEnum};
 use figment::{
     Figment,
     providers::{Env, Format, Serialized, Toml},
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// Random Photo from Pexels :`city_sunset`
@@ -26,11 +28,12 @@ pub struct Cli {
     #[arg(short, long, value_enum)]
     pub renderer: Option<RendererType>,
 
-    /// Optional: Force specific outputs for Sway
+    /// Optional: Only use outputs matching these globs (name, make, model or
+    /// serial); prefix with `!` to exclude. Sway and Hyprland only
     #[arg(short, long)]
     pub outputs: Option<Vec<String>>,
 
-    #[arg(short, long)]
+    #[arg(long)]
     pub transition_type: Option<String>,
 
     #[arg(short = 's', long)]
@@ -46,6 +49,36 @@ pub struct Cli {
     /// If true, run as a persistent daemon. Requires `time` to be set.
     #[arg(long, default_value_t = false)]
     pub daemon: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    /// Print the theme extracted from an image without applying anything
+    Palette {
+        /// Image to extract the palette from
+        image: PathBuf,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = PaletteFormat::Term)]
+        format: PaletteFormat,
+
+        /// Where to write the PNG swatch strip (`--format png` only)
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum PaletteFormat {
+    /// Roles and 16 colors as JSON
+    Json,
+    /// Truecolor swatches in the terminal
+    Term,
+    /// A generated PNG swatch strip
+    Png,
 }
 
 // Merge CLI Overrides
@@ -83,8 +116,39 @@ struct CliOverrides {
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum BackendType {
+    /// Detect the compositor from the session environment
+    Auto,
+    /// GNOME; also sets the wallpaper, overriding `renderer`
+    Gnome,
     Hyprland,
+    /// KDE Plasma; also sets the wallpaper, overriding `renderer`
+    Kde,
+    Niri,
     Sway,
+    /// Any compositor with wlr-output-management or named `wl_output`s
+    Wayland,
+    /// X11 window managers, via `xrandr`
+    X11,
+}
+
+impl BackendType {
+    /// Desktop environments own the wallpaper, so their backend is also the renderer.
+    #[must_use]
+    pub const fn renderer(self) -> Option<RendererType> {
+        match self {
+            Self::Gnome => Some(RendererType::Gnome),
+            Self::Kde => Some(RendererType::Kde),
+            _ => None,
+        }
+    }
+}
+
+impl RendererType {
+    /// Whether this renderer draws on an X11 root window.
+    #[must_use]
+    pub const fn is_x11(self) -> bool {
+        matches!(self, Self::Feh | Self::Xwallpaper)
+    }
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Serialize, Deserialize)]
@@ -94,6 +158,14 @@ pub enum RendererType {
     #[clap(alias = "swww")]
     #[serde(alias = "swww")]
     Awww,
+    /// X11: one image per screen via `feh --bg-*`
+    Feh,
+    /// X11: per-output wallpapers via `xwallpaper --output`
+    Xwallpaper,
+    /// `org.gnome.desktop.background` (implied by `--backend gnome`)
+    Gnome,
+    /// Plasma's `evaluateScript` (implied by `--backend kde`)
+    Kde,
 }
 
 /// The final configuration used by the application
@@ -108,6 +180,378 @@ pub struct Config {
     pub transition_step: u8,
     pub transition_fps: u8,
     pub daemon: bool,
+    pub theme: ThemeConfig,
+}
+
+/// Options for the generated theme files, read from the `[theme]` table.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// Push the accent and inactive colors to the compositor's window borders.
+    pub borders: bool,
+    /// Which on-screen wallpaper the theme is extracted from.
+    pub follow: ThemeFollow,
+    /// Output used by `follow = "primary"`; defaults to the first active monitor.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primary_output: Option<String>,
+    /// Where the colors come from: `"extract"` or `"scheme:<file.yaml>"`.
+    pub source: ThemeSource,
+    /// Directory for the generated theme files; defaults to `~/.config/randpaper/themes`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+    /// Per-directory overrides of `source`, read from `[theme.directories]`.
+    pub directories: BTreeMap<PathBuf, ThemeSource>,
+    /// Upper bound for one theme update (e.g., "10s"); the rotation moves on without it.
+    pub timeout: String,
+    /// Optional color fade between the previous and the new theme.
+    pub animation: AnimationConfig,
+    /// Adjustments applied to extracted palettes.
+    pub tuning: TuningConfig,
+    /// Per-application outputs, read from `[theme.targets.<name>]`.
+    pub targets: Targets,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            borders: false,
+            follow: ThemeFollow::Primary,
+            primary_output: None,
+            source: ThemeSource::Extract,
+            dir: None,
+            directories: BTreeMap::new(),
+            timeout: "10s".to_string(),
+            animation: AnimationConfig::default(),
+            tuning: TuningConfig::default(),
+            targets: Targets::default(),
+        }
+    }
+}
+
+/// `[theme.animation]`: fades theme targets instead of switching colors at once.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct AnimationConfig {
+    pub enabled: bool,
+    /// Length of the fade (e.g., "800ms"); defaults to the length of the awww transition.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<String>,
+    /// Frames per second, capped at 30. Each frame rewrites and reloads the kitty and foot targets.
+    pub fps: u8,
+}
+
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            duration: None,
+            fps: 10,
+        }
+    }
+}
+
+/// `[theme.tuning]`: palette adjustments made in `OKLCh` before roles are picked.
+///
+/// Only extracted palettes are tuned; schemes and pinned overrides are used as written.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct TuningConfig {
+    /// Multiplies every color's chroma (1.0 leaves colors unchanged).
+    pub saturation_boost: f32,
+    /// Raises tinted colors to at least this chroma (0.0-0.37); grays are left alone.
+    pub min_chroma: f32,
+    /// Rotates the accent role's hue, in degrees.
+    pub accent_hue_shift: f32,
+    /// Clamps lightness to `[min, max]`, both within 0.0-1.0.
+    pub brightness_range: [f32; 2],
+}
+
+impl Default for TuningConfig {
+    fn default() -> Self {
+        Self {
+            saturation_boost: 1.0,
+            min_chroma: 0.0,
+            accent_hue_shift: 0.0,
+            brightness_range: [0.0, 1.0],
+        }
+    }
+}
+
+impl TuningConfig {
+    /// Returns true if tuning would leave a palette unchanged.
+    #[must_use]
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Where a theme's colors come from.
+///
+/// Written as `"extract"` (palette extraction from the wallpaper) or
+/// `"scheme:<file.yaml>"` (a base16/base24 scheme file; a leading `~/` is expanded).
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ThemeSource {
+    Extract,
+    Scheme(PathBuf),
+}
+
+impl TryFrom<String> for ThemeSource {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        if s == "extract" {
+            return Ok(Self::Extract);
+        }
+        match s.strip_prefix("scheme:") {
+            Some(path) if !path.is_empty() => Ok(Self::Scheme(PathBuf::from(path))),
+            _ => Err(format!(
+                "invalid theme source `{s}`: expected \"extract\" or \"scheme:<file.yaml>\""
+            )),
+        }
+    }
+}
+
+impl From<ThemeSource> for String {
+    fn from(source: ThemeSource) -> Self {
+        match source {
+            ThemeSource::Extract => "extract".to_string(),
+            ThemeSource::Scheme(path) => format!("scheme:{}", path.display()),
+        }
+    }
+}
+
+/// Selects which monitor's wallpaper drives the theme.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeFollow {
+    /// The wallpaper on `primary_output` (or the first monitor).
+    Primary,
+    /// The wallpaper on the currently focused monitor.
+    Focused,
+    /// A blend of every monitor's palette.
+    Blend,
+}
+
+/// The built-in theme outputs. Each one can be disabled, redirected or given
+/// a different reload action.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct Targets {
+    pub waybar: TargetConfig,
+    pub ghostty: TargetConfig,
+    pub kitty: TargetConfig,
+    pub foot: TargetConfig,
+    pub alacritty: TargetConfig,
+    /// `wezterm` color scheme; defaults to `~/.config/wezterm/colors/randpaper.toml`.
+    pub wezterm: TargetConfig,
+    /// X resources for st, urxvt and xterm, merged with `xrdb` when it is installed.
+    pub xresources: TargetConfig,
+    /// pywal output set; `path` is the directory (default `~/.cache/wal`).
+    pub pywal: TargetConfig,
+    pub gtk: TargetConfig,
+    /// KDE scheme and qt5ct/qt6ct palette; `path` is the directory they are written to.
+    pub qt: TargetConfig,
+    /// swaylock config pointing at a blurred, dimmed copy of the wallpaper.
+    pub swaylock: TargetConfig,
+    /// hyprlock variables (`$rp_wallpaper`, `$rp_accent`, ...) for `source =`.
+    pub hyprlock: TargetConfig,
+    pub mako: TargetConfig,
+    pub dunst: TargetConfig,
+    pub fuzzel: TargetConfig,
+    /// rofi `.rasi` variables (`@rp-bg`, `@rp-accent`, ...).
+    pub rofi: TargetConfig,
+    /// A complete wofi stylesheet.
+    pub wofi: TargetConfig,
+    /// Neovim colorscheme; `path` is the `colors/randpaper.lua` file.
+    pub neovim: TargetConfig,
+    pub tmux: TargetConfig,
+    pub btop: TargetConfig,
+    /// Shell snippet exporting `FZF_DEFAULT_OPTS`.
+    pub fzf: TargetConfig,
+    /// `[palettes.randpaper]` in `starship.toml`, edited in place.
+    pub starship: TargetConfig,
+}
+
+impl Default for Targets {
+    fn default() -> Self {
+        Self {
+            waybar: TargetConfig::enabled(ReloadAction::signal("USR2", "waybar")),
+            ghostty: TargetConfig::enabled(ReloadAction::signal("USR2", "ghostty")),
+            kitty: TargetConfig::enabled(ReloadAction::Kitty {
+                socket: "unix:/tmp/mykitty".to_string(),
+            }),
+            foot: TargetConfig::enabled(ReloadAction::shell(
+                "pkill -USR1 foot; sleep 0.05; pkill -USR1 foot; true",
+            )),
+            // Alacritty watches imported files itself
+            alacritty: TargetConfig::disabled(),
+            wezterm: TargetConfig::disabled(),
+            // Merging restyles every running X client, so it is opt-in
+            xresources: TargetConfig::opt_in(ReloadAction::shell(
+                r#"command -v xrdb >/dev/null && [ -n "$DISPLAY" ] || exit 0; xrdb -merge "$1""#,
+            )),
+            pywal: TargetConfig::disabled(),
+            gtk: TargetConfig::disabled(),
+            qt: TargetConfig::disabled(),
+            swaylock: TargetConfig::disabled(),
+            hyprlock: TargetConfig::disabled(),
+            // Only reload daemons that are running, so an unused one never logs a warning
+            mako: TargetConfig::opt_in(ReloadAction::shell(
+                "pgrep -x mako >/dev/null || exit 0; makoctl reload",
+            )),
+            dunst: TargetConfig::opt_in(ReloadAction::shell(
+                "pgrep -x dunst >/dev/null || exit 0; dunstctl reload",
+            )),
+            // Launchers read their config on every start
+            fuzzel: TargetConfig::disabled(),
+            rofi: TargetConfig::disabled(),
+            wofi: TargetConfig::disabled(),
+            // Reloading restyles running editors and tmux servers, so these are opt-in
+            neovim: TargetConfig::opt_in(ReloadAction::Neovim { socket: None }),
+            tmux: TargetConfig::opt_in(ReloadAction::shell(
+                r#"tmux has-session 2>/dev/null || exit 0; tmux source-file "$1""#,
+            )),
+            btop: TargetConfig::disabled(),
+            fzf: TargetConfig::disabled(),
+            // Edits the user's own starship.toml, so it is opt-in
+            starship: TargetConfig::disabled(),
+        }
+    }
+}
+
+#[cfg(not(feature = "extract"))]
+impl Targets {
+    /// Turns off the lock targets: their blurred image needs the `image` crate,
+    /// so without the `extract` feature they would fail on every rotation.
+    fn drop_lock_targets(&mut self) {
+        for (name, target) in [
+            ("swaylock", &mut self.swaylock),
+            ("hyprlock", &mut self.hyprlock),
+        ] {
+            if target.enabled {
+                log::warn!("Disabling the {name} target: built without the `extract` feature");
+                target.enabled = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+impl Targets {
+    /// Every target disabled, so tests never write app configs or signal processes.
+    #[must_use]
+    pub fn none() -> Self {
+        let mut targets = Self::default();
+        for target in [
+            &mut targets.waybar,
+            &mut targets.ghostty,
+            &mut targets.kitty,
+            &mut targets.foot,
+            &mut targets.alacritty,
+            &mut targets.wezterm,
+            &mut targets.xresources,
+            &mut targets.pywal,
+            &mut targets.gtk,
+            &mut targets.qt,
+            &mut targets.swaylock,
+            &mut targets.hyprlock,
+            &mut targets.mako,
+            &mut targets.dunst,
+            &mut targets.fuzzel,
+            &mut targets.rofi,
+            &mut targets.wofi,
+            &mut targets.neovim,
+            &mut targets.tmux,
+            &mut targets.btop,
+            &mut targets.fzf,
+            &mut targets.starship,
+        ] {
+            *target = TargetConfig::disabled();
+        }
+        targets
+    }
+}
+
+/// A single theme output: whether it is written, where, and how the consuming app is reloaded.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TargetConfig {
+    pub enabled: bool,
+    /// Overrides the default output location. A leading `~/` is expanded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    #[serde(default)]
+    pub reload: ReloadAction,
+}
+
+impl TargetConfig {
+    const fn enabled(reload: ReloadAction) -> Self {
+        Self {
+            enabled: true,
+            path: None,
+            reload,
+        }
+    }
+
+    /// Off until the user sets `enabled = true`, which keeps `reload`.
+    const fn opt_in(reload: ReloadAction) -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            reload,
+        }
+    }
+
+    const fn disabled() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            reload: ReloadAction::None,
+        }
+    }
+}
+
+/// How an application is told to pick up its regenerated theme file.
+///
+/// Written in TOML as e.g. `reload = { type = "signal", signal = "USR2", process = "waybar" }`.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ReloadAction {
+    /// Write the file only.
+    #[default]
+    None,
+    /// Send `signal` (e.g. `USR2`) to every process named exactly `process`.
+    Signal { signal: String, process: String },
+    /// Run a command; `{path}` in any argument is replaced with the output path.
+    Command { argv: Vec<String> },
+    /// Run `kitten @ set-colors` against every socket matching `socket` (globs allowed).
+    Kitty { socket: String },
+    /// Briefly switch the GTK theme via `gsettings` so GTK apps re-read `gtk.css`.
+    GtkTheme,
+    /// Re-apply the `randpaper` colorscheme in every Neovim listening on a socket
+    /// matching `socket` (default `$XDG_RUNTIME_DIR/nvim.*`) that already uses it.
+    Neovim {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        socket: Option<String>,
+    },
+}
+
+impl ReloadAction {
+    /// Runs `script` with `sh -c`; the output path is available as `$1`.
+    fn shell(script: &str) -> Self {
+        Self::Command {
+            argv: ["sh", "-c", script, "sh", "{path}"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+
+    fn signal(signal: &str, process: &str) -> Self {
+        Self::Signal {
+            signal: signal.to_string(),
+            process: process.to_string(),
+        }
+    }
 }
 
 impl Default for Config {
@@ -115,21 +559,25 @@ impl Default for Config {
         Self {
             wallpaper_dir: PathBuf::from("."),
             time: None,
-            backend: BackendType::Sway,
+            backend: BackendType::Auto,
             renderer: RendererType::Swaybg,
             outputs: Vec::new(),
             transition_type: "simple".to_string(),
             transition_step: 90,
             transition_fps: 30,
             daemon: false,
+            theme: ThemeConfig::default(),
         }
     }
 }
 
 impl Config {
-    pub fn new() -> anyhow::Result<Self> {
-        let cli = Cli::parse();
-
+    /// Merges the defaults, the config file, `cli` and `RANDPAPER_*` variables.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`] if the config file or a setting cannot be parsed.
+    pub fn new(cli: Cli) -> Result<Self> {
         let mut builder = Figment::new().merge(Serialized::defaults(Self::default()));
 
         // 1. Determine config file path
@@ -162,6 +610,13 @@ impl Config {
         // 4. Merge Environment Variables (optional, but good practice)
         builder = builder.merge(Env::prefixed("RANDPAPER_"));
 
-        Ok(builder.extract()?)
+        let config: Self = builder.extract().map_err(|e| Error::Config(e.into()))?;
+        #[cfg(not(feature = "extract"))]
+        let config = {
+            let mut config = config;
+            config.theme.targets.drop_lock_targets();
+            config
+        };
+        Ok(config)
     }
 }
@@ -1,6 +1,8 @@
 use crate::cli::Config;
-use crate::theme::update_theme_file;
-use crate::traits::Backend;
+use crate::error::{Error, Result};
+use crate::rotation::{Rotation, focused_monitor};
+use crate::theme::{Animation, PendingTheme, apply_pending, update_theme};
+use crate::traits::{Backend, Render};
 use crate::wallpaper::WallpaperCache;
 
 use std::time::Duration;
@@ -8,10 +10,43 @@ use tokio::signal::unix::{SignalKind, signal};
 use tokio::time::sleep;
 
 mod render;
+// The tests draw their wallpapers with the `image` crate
+#[cfg(all(test, feature = "extract"))]
+mod tests;
 
-// Re-exporting for use in `oneshot_mode()` in `main.rs`
-pub use render::awww::detect_awww_binary;
-pub use render::awww::ensure_awww_daemon;
+pub use render::{Renderer, render_once};
+
+/// How long to wait before asking an unreachable compositor for monitors again.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// A rotation decided ahead of time, with its theme extraction already running.
+struct Prepared {
+    rotation: Rotation,
+    /// The focused monitor the theme images were chosen for.
+    focused: Option<String>,
+    theme: PendingTheme,
+}
+
+impl Prepared {
+    fn new(
+        config: &Config,
+        cache: &WallpaperCache,
+        monitors: &[String],
+        focused: Option<String>,
+    ) -> Self {
+        let rotation = Rotation::pick(cache, monitors);
+        let images = rotation.theme_images(
+            config.theme.follow,
+            config.theme.primary_output.as_deref(),
+            focused.as_deref(),
+        );
+        Self {
+            theme: PendingTheme::spawn(images, &config.theme),
+            rotation,
+            focused,
+        }
+    }
+}
 
 /// Runs the persistent background process that cycles wallpapers and themes.
 ///
@@ -19,54 +54,156 @@ pub use render::awww::ensure_awww_daemon;
 /// 1. Initializes the wallpaper cache and determines the rotation frequency.
 /// 2. Sets up a listener for `SIGUSR1` to allow manual skips.
 /// 3. Enters an infinite loop that updates themes and wallpapers based on the timer.
-pub async fn run_loop<B: Backend>(config: Config, backend: B) -> anyhow::Result<()> {
+///
+/// # Errors
+///
+/// Returns [`Error::Config`] if `config.time` is missing or invalid or an animation setting is invalid,
+/// and otherwise whatever stops the loop: the renderer failing to start or draw,
+/// an unreadable wallpaper directory, or the skip signal being unavailable.
+/// Backend and theme failures are logged and retried instead.
+pub async fn run_loop<B: Backend>(config: Config, backend: B) -> Result<()> {
+    // Initialize the chosen rendering engine (swaybg or awww)
+    let renderer = Renderer::new(&config).await?;
+    run_with_renderer(config, backend, renderer, RETRY_DELAY).await
+}
+
+/// Sets one wallpaper per monitor and updates the theme once.
+///
+/// Detects monitors via `backend`, picks a random wallpaper for each, updates
+/// the theme targets and invokes the selected renderer through [`render_once`].
+///
+/// # Errors
+///
+/// Returns the first failure of the backend, the renderer or the theme update.
+pub async fn run_once<B: Backend>(config: &Config, backend: &B) -> Result<()> {
+    log::info!("One-shot mode: picking wallpaper once and exiting");
+
+    // Initialize the wallpaper cache from the provided directory
+    let cache = WallpaperCache::new(&config.wallpaper_dir)?;
+    let animation = Animation::from_config(config)?;
+
+    // 1. Identify active monitors based on the user-selected backend
+    let monitors = backend.get_active_monitors().await?;
+
+    // 2. Decide every monitor's wallpaper up front so the theme follows what is on screen
+    let rotation = Rotation::pick(&cache, &monitors);
+    let focused = focused_monitor(config.theme.follow, backend).await;
+    let theme_images = rotation.theme_images(
+        config.theme.follow,
+        config.theme.primary_output.as_deref(),
+        focused.as_deref(),
+    );
+
+    // 3. Generate the theme files (Waybar, Terminals) off the runtime while
+    //    the renderer applies the wallpapers
+    let (theme, rendered) = tokio::join!(
+        update_theme(theme_images, config.theme.clone(), animation),
+        render_once(config, &rotation),
+    );
+    rendered?;
+    let theme = theme?;
+
+    // 4. Optional: let window borders follow the wallpaper too
+    if config.theme.borders {
+        backend.set_border_colors(&theme.roles).await?;
+    }
+
+    log::info!("Wallpaper and theme updated. Exiting.");
+    Ok(())
+}
+
+/// The daemon loop behind [`run_loop`], drawing wallpapers with `renderer` and
+/// waiting `retry_delay` between attempts to reach the compositor.
+async fn run_with_renderer<B: Backend, R: Render>(
+    config: Config,
+    backend: B,
+    mut renderer: R,
+    retry_delay: Duration,
+) -> Result<()> {
     // Ensure the fallback theme is present before the first rotation
-    crate::theme::ensure_theme_exists()?;
+    crate::theme::ensure_theme_exists(&config.theme)?;
 
     let cache = WallpaperCache::new(&config.wallpaper_dir)?;
 
     // Parse the human-readable duration (e.g., "30m", "1h") into a Duration object
-    let period: Duration =
-        parse_duration::parse(config.time.as_ref().expect("daemon mode requires --time"))
-            .map_err(|e| anyhow::anyhow!("invalid duration: {e}"))?;
-
-    // Initialize the chosen rendering engine (swaybg or awww)
-    let mut renderer = render::Renderer::new(&config).await?;
+    let time = config
+        .time
+        .as_ref()
+        .ok_or_else(|| Error::Config(anyhow::anyhow!("daemon mode requires `time`")))?;
+    let period: Duration = parse_duration::parse(time)
+        .map_err(|e| Error::Config(anyhow::anyhow!("invalid duration: {e}")))?;
 
-    // if cli.renderer == RendererType::Swww {
-    // optional: log renderer init already ensured daemon
-    // }
+    // Resolve the theme fade once; it only depends on the config
+    let animation = Animation::from_config(&config)?;
 
     // Set up a signal listener for SIGUSR1 (allows users to run `pkill -USR1 randpaper`)
-    let mut sig_usr1 = signal(SignalKind::user_defined1())?;
+    let mut sig_usr1 = signal(SignalKind::user_defined1()).map_err(Error::Signal)?;
+
+    // Every rotation plans the next one so its palette is ready before the timer fires
+    let mut next: Option<Prepared> = None;
 
     loop {
         // Fetch active monitors; if the compositor is temporarily unreachable,
-        // wait 5 seconds and retry rather than crashing the daemon.
+        // wait a few seconds and retry rather than crashing the daemon.
         let monitors = match backend.get_active_monitors().await {
             Ok(m) => m,
             Err(e) => {
-                log::error!("Failed to get monitors: {e}. Retrying in 5s...");
-                sleep(Duration::from_secs(5)).await;
+                log::error!("Failed to get monitors: {e}. Retrying in {retry_delay:?}...");
+                sleep(retry_delay).await;
                 continue;
             }
         };
 
-        // Select a random wallpaper and update system-wide theme colors
-        let img = cache.pick_random();
-        let _ = update_theme_file(img);
+        // Use the rotation prepared during the last idle period, unless the
+        // monitors or focus changed since it was planned
+        let focused = focused_monitor(config.theme.follow, &backend).await;
+        let prepared = match next.take() {
+            Some(p) if p.rotation.is_for(&monitors) && p.focused == focused => p,
+            stale => {
+                if stale.is_some() {
+                    log::debug!("Monitors or focus changed since last rotation; re-planning");
+                }
+                Prepared::new(&config, &cache, &monitors, focused.clone())
+            }
+        };
+
+        // Apply the theme while the renderer dispatches the wallpaper update
+        let (theme, render_result) = tokio::join!(
+            apply_pending(prepared.theme, config.theme.clone(), animation),
+            renderer.apply(&config, &prepared.rotation),
+        );
 
-        // Dispatch the wallpaper update to the specific renderer
-        renderer.apply(&config, &cache, &monitors).await?;
+        // Plan the next rotation now so its palette is extracted while we sleep
+        next = Some(Prepared::new(&config, &cache, &monitors, focused));
+        render_result?;
+
+        match theme {
+            Ok(theme) if config.theme.borders => {
+                if let Err(e) = backend.set_border_colors(&theme.roles).await {
+                    log::warn!("Failed to set border colors: {e:#}");
+                }
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Theme update failed: {e:#}"),
+        }
 
         // The core wait logic:
         // Either wait for the full 'period' duration, OR
-        // break out early if a SIGUSR1 is received.
+        // break out early if a SIGUSR1 is received or a monitor is plugged in/out.
+        let output_change = async {
+            if let Err(e) = backend.wait_for_output_change().await {
+                log::warn!("Output events unavailable ({e:#}); waiting for the timer only");
+                std::future::pending::<()>().await;
+            }
+        };
         tokio::select! {
             () = sleep(period) => {}
             _ = sig_usr1.recv() => {
                 log::info!("Received skip signal (SIGUSR1). Cycling wallpaper immediately.");
             }
+            () = output_change => {
+                log::info!("Outputs changed. Cycling wallpaper immediately.");
+            }
         }
     }
 }
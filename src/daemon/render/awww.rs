@@ -1,6 +1,8 @@
 use crate::cli::Config;
-use crate::wallpaper::WallpaperCache;
+use crate::overrides;
+use crate::rotation::Rotation;
 use anyhow::Context;
+use std::path::Path;
 use std::time::Duration;
 use tokio::process::Command;
 use tokio::time::sleep;
@@ -58,36 +60,55 @@ pub async fn ensure_awww_daemon(awww_bin: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Builds the `--resize` flag for `img`'s scaling mode.
+///
+/// Modes awww cannot express are logged and left to awww's default.
+fn resize_args(img: &Path) -> Vec<&'static str> {
+    let mode = overrides::scale_mode(img);
+    mode.awww_resize().map_or_else(
+        || {
+            log::warn!(
+                "awww has no `{}` scaling mode; using its default",
+                mode.swaybg()
+            );
+            Vec::new()
+        },
+        |resize| vec!["--resize", resize],
+    )
+}
+
+/// Constructs the `img` arguments that put `img` on `monitor`.
+///
+/// Transition settings (type, step, fps) come from the config and the
+/// `--resize` flag from the image's scaling mode.
+pub fn build_awww_args(config: &Config, monitor: &str, img: &Path) -> Vec<String> {
+    let mut args = vec![
+        "img".to_string(),
+        img.to_string_lossy().to_string(),
+        "-o".to_string(),
+        monitor.to_string(),
+        "--transition-type".to_string(),
+        config.transition_type.clone(),
+        "--transition-step".to_string(),
+        config.transition_step.to_string(),
+        "--transition-fps".to_string(),
+        config.transition_fps.to_string(),
+    ];
+    args.extend(resize_args(img).into_iter().map(String::from));
+    args
+}
+
 /// Sends commands to the `awww` daemon to update wallpapers with transitions.
 ///
-/// This loops through each monitor and calls the `img` command.
+/// This loops through each monitor/image assignment and calls the `img` command.
 /// It uses transition settings (type, step, fps) provided in the `Cli` config.
 ///
 /// # Errors
 /// Returns an error if the binary cannot be executed or if `awww` returns a non-zero exit code.
-pub async fn apply(
-    config: &Config,
-    cache: &WallpaperCache,
-    monitors: &[String],
-    awww_bin: &str,
-) -> anyhow::Result<()> {
-    let step = config.transition_step.to_string();
-    let fps = config.transition_fps.to_string();
-
-    for monitor in monitors {
-        let img = cache.pick_random();
-
+pub async fn apply(config: &Config, rotation: &Rotation, awww_bin: &str) -> anyhow::Result<()> {
+    for (monitor, img) in rotation.assignments() {
         let out = Command::new(awww_bin)
-            .arg("img")
-            .arg(img)
-            .arg("-o")
-            .arg(monitor)
-            .arg("--transition-type")
-            .arg(&config.transition_type)
-            .arg("--transition-step")
-            .arg(&step)
-            .arg("--transition-fps")
-            .arg(&fps)
+            .args(build_awww_args(config, monitor, img))
             .output()
             .await
             .with_context(|| format!("failed to run {awww_bin}"))?;
@@ -102,3 +123,53 @@ pub async fn apply(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{StubDir, touch};
+
+    #[test]
+    fn builds_img_args_with_transition_and_resize() {
+        let dir = StubDir::new();
+        let img = dir.path("a.png");
+        touch(&img);
+        std::fs::write(dir.path("a.png.randpaper.toml"), "mode = \"center\"\n").expect("sidecar");
+        let config = Config {
+            transition_type: "wipe".to_string(),
+            transition_step: 45,
+            transition_fps: 60,
+            ..Config::default()
+        };
+
+        let path = img.display().to_string();
+        assert_eq!(
+            build_awww_args(&config, "DP-1", &img),
+            [
+                "img",
+                &path,
+                "-o",
+                "DP-1",
+                "--transition-type",
+                "wipe",
+                "--transition-step",
+                "45",
+                "--transition-fps",
+                "60",
+                "--resize",
+                "no",
+            ]
+        );
+    }
+
+    #[test]
+    fn leaves_resize_out_for_modes_awww_lacks() {
+        let dir = StubDir::new();
+        let img = dir.path("a.png");
+        touch(&img);
+        std::fs::write(dir.path("a.png.randpaper.toml"), "mode = \"tile\"\n").expect("sidecar");
+
+        let args = build_awww_args(&Config::default(), "DP-1", &img);
+        assert!(!args.iter().any(|a| a == "--resize"), "{args:?}");
+    }
+}
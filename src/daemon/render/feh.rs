@@ -0,0 +1,126 @@
+use crate::overrides;
+use crate::rotation::Rotation;
+use anyhow::{Context, bail};
+use std::path::PathBuf;
+use tokio::process::Command;
+
+/// Constructs the command-line arguments for `feh`.
+///
+/// feh gives the n-th image to the n-th Xinerama screen and takes a single
+/// scaling flag for all of them, so the first image's mode wins. `--no-fehbg`
+/// keeps it from rewriting `~/.fehbg` on every rotation.
+///
+/// Because images are matched to screens by position, an image that cannot be
+/// canonicalized keeps its slot with the previous (or first) usable image, so
+/// the monitors after it still get their own wallpapers.
+pub fn build_feh_args(assignments: &[(String, PathBuf)]) -> Vec<String> {
+    let resolved: Vec<Option<PathBuf>> = assignments
+        .iter()
+        .map(|(_, img)| img.canonicalize().ok())
+        .collect();
+    let Some(first) = resolved.iter().flatten().next().cloned() else {
+        return Vec::new();
+    };
+
+    let mut fallback = first.clone();
+    let images: Vec<PathBuf> = assignments
+        .iter()
+        .zip(resolved)
+        .map(|((monitor, img), abs)| {
+            if let Some(abs) = abs {
+                fallback = abs;
+            } else {
+                log::warn!(
+                    "{} is unavailable; reusing {} on {monitor}",
+                    img.display(),
+                    fallback.display()
+                );
+            }
+            fallback.clone()
+        })
+        .collect();
+
+    let mut args = vec![
+        "--no-fehbg".to_string(),
+        overrides::scale_mode(&first).feh().to_string(),
+    ];
+    args.extend(images.iter().map(|img| img.to_string_lossy().to_string()));
+    args
+}
+
+/// Applies wallpapers by setting the X root window with `feh`.
+///
+/// # Errors
+/// Returns an error if `feh` cannot be run or fails.
+pub async fn apply(rotation: &Rotation) -> anyhow::Result<()> {
+    let args = build_feh_args(rotation.assignments());
+    if args.is_empty() {
+        return Ok(());
+    }
+
+    let out = Command::new("feh")
+        .args(&args)
+        .output()
+        .await
+        .context("failed to run 'feh'. Is it installed and in your PATH?")?;
+    if !out.status.success() {
+        bail!(
+            "feh failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{StubDir, touch};
+    use std::path::Path;
+
+    fn abs(p: &Path) -> String {
+        p.canonicalize().expect("path").display().to_string()
+    }
+
+    #[test]
+    fn passes_images_in_screen_order_with_the_first_mode() {
+        let dir = StubDir::new();
+        let (a, b) = (dir.path("a.png"), dir.path("b.jpg"));
+        touch(&a);
+        touch(&b);
+        std::fs::write(dir.path("a.png.randpaper.toml"), "mode = \"fit\"\n").expect("sidecar");
+
+        let args = build_feh_args(&[
+            ("DP-1".to_string(), a.clone()),
+            ("HDMI-1".to_string(), b.clone()),
+        ]);
+        assert_eq!(args, ["--no-fehbg", "--bg-max", &abs(&a), &abs(&b)]);
+    }
+
+    #[test]
+    fn keeps_the_slot_of_a_missing_image() {
+        let dir = StubDir::new();
+        let (a, c) = (dir.path("a.png"), dir.path("c.png"));
+        touch(&a);
+        touch(&c);
+
+        let args = build_feh_args(&[
+            ("DP-1".to_string(), dir.path("gone.png")),
+            ("DP-2".to_string(), a.clone()),
+            ("DP-3".to_string(), dir.path("gone-too.png")),
+            ("DP-4".to_string(), c.clone()),
+        ]);
+        assert_eq!(
+            args,
+            [
+                "--no-fehbg",
+                "--bg-fill",
+                &abs(&a),
+                &abs(&a),
+                &abs(&a),
+                &abs(&c)
+            ]
+        );
+        assert!(build_feh_args(&[("DP-1".to_string(), dir.path("gone.png"))]).is_empty());
+    }
+}
@@ -1,19 +1,29 @@
+use crate::backends::gnome::GnomeBackend;
+use crate::backends::kde::KdeBackend;
 use crate::cli::{Config, RendererType};
-use crate::wallpaper::WallpaperCache;
-use tokio::process::Child;
+use crate::error::{Error, Result};
+use crate::overrides;
+use crate::rotation::Rotation;
+use crate::traits::Render;
+use anyhow::Context;
+use async_trait::async_trait;
+use tokio::process::{Child, Command};
 
 pub mod awww;
-mod swaybg;
+pub mod feh;
+pub mod swaybg;
+pub mod xwallpaper;
 
 /// Manages the lifecycle and execution of wallpaper rendering backends.
 ///
-/// The `Renderer` abstracts over different wallpaper utilities (like `awww` or `swaybg`),
-/// handling process management for long-running children and binary detection.
+/// The `Renderer` abstracts over different wallpaper utilities (like `awww`, `swaybg` or `feh`)
+/// and desktop environments (GNOME, Plasma), handling process management for
+/// long-running children and binary detection.
 pub struct Renderer {
     /// Holds a reference to the active `swaybg` process, if running.
     /// This allows the renderer to kill the old process before starting a new one.
     swaybg_child: Option<Child>,
-    /// The path to the detected `awww` binary.
+    /// The path to the detected `awww` binary, once its daemon is known to run.
     awww_bin: Option<String>,
 }
 
@@ -26,22 +36,35 @@ impl Renderer {
     ///
     /// # Errors
     ///
-    /// Returns an error if the renderer initialization (e.g., starting the daemon) fails.
-    pub async fn new(config: &Config) -> anyhow::Result<Self> {
-        let awww_bin = match config.renderer {
-            RendererType::Awww => {
-                let bin = awww::detect_awww_binary().await;
-                awww::ensure_awww_daemon(&bin).await?;
-                Some(bin)
-            }
-            RendererType::Swaybg => None,
-        };
-        Ok(Self {
+    /// Returns [`Error::Render`] if the renderer initialization (e.g., starting the daemon) fails.
+    pub async fn new(config: &Config) -> Result<Self> {
+        let mut renderer = Self {
             swaybg_child: None,
-            awww_bin,
-        })
+            awww_bin: None,
+        };
+        if config.renderer == RendererType::Awww {
+            renderer.awww_bin().await.map_err(Error::Render)?;
+        }
+        Ok(renderer)
+    }
+
+    /// The `awww` binary, detected and its daemon started on first use.
+    ///
+    /// [`Renderer::new`] does this up front for the `Awww` renderer; a config
+    /// switched to `Awww` later is set up here instead.
+    async fn awww_bin(&mut self) -> anyhow::Result<String> {
+        if let Some(bin) = &self.awww_bin {
+            return Ok(bin.clone());
+        }
+        let bin = awww::detect_awww_binary().await;
+        awww::ensure_awww_daemon(&bin).await?;
+        self.awww_bin = Some(bin.clone());
+        Ok(bin)
     }
+}
 
+#[async_trait]
+impl Render for Renderer {
     /// Applies the current wallpaper configuration to the specified monitors.
     ///
     /// This method routes the request to the appropriate backend module based on
@@ -49,25 +72,100 @@ impl Renderer {
     ///
     /// # Arguments
     ///
-    /// * `cli` - The global command-line configuration.
-    /// * `cache` - The cache containing the wallpaper images to be displayed.
-    /// * `monitors` - A list of monitor names (e.g., "eDP-1", "DP-2") to update.
-    ///
-    /// # Panics
-    ///
-    /// Panics if the renderer is set to `Awww` but the binary path was never initialized.
-    pub async fn apply(
-        &mut self,
-        config: &Config,
-        cache: &WallpaperCache,
-        monitors: &[String],
-    ) -> anyhow::Result<()> {
-        match config.renderer {
-            RendererType::Swaybg => swaybg::apply(cache, monitors, &mut self.swaybg_child).await,
-            RendererType::Awww => {
-                let bin = self.awww_bin.as_deref().expect("Renderer::new sets this");
-                awww::apply(config, cache, monitors, bin).await
+    /// * `config` - The global configuration.
+    /// * `rotation` - The monitor/image assignments decided for this rotation.
+    async fn apply(&mut self, config: &Config, rotation: &Rotation) -> Result<()> {
+        let result = match config.renderer {
+            RendererType::Swaybg => swaybg::apply(rotation, &mut self.swaybg_child).await,
+            RendererType::Awww => match self.awww_bin().await {
+                Ok(bin) => awww::apply(config, rotation, &bin).await,
+                Err(e) => Err(e),
+            },
+            RendererType::Feh => feh::apply(rotation).await,
+            RendererType::Xwallpaper => xwallpaper::apply(rotation).await,
+            // The desktop backends already report `Error::Render`
+            RendererType::Gnome => {
+                return GnomeBackend::default()
+                    .set_wallpaper(rotation.assignments())
+                    .await;
+            }
+            RendererType::Kde => {
+                return KdeBackend::default()
+                    .set_wallpaper(rotation.assignments())
+                    .await;
+            }
+        };
+        result.map_err(Error::Render)
+    }
+}
+
+/// Applies `rotation` once and leaves the wallpaper tool running on its own.
+///
+/// Unlike [`Renderer`], nothing is kept around to replace the wallpapers later:
+/// `swaybg` is started detached after killing any earlier instance.
+///
+/// # Errors
+///
+/// Returns [`Error::Render`] if the wallpaper tool cannot be started or fails.
+pub async fn render_once(config: &Config, rotation: &Rotation) -> Result<()> {
+    match config.renderer {
+        RendererType::Swaybg => {
+            let args = swaybg::build_swaybg_args(rotation.assignments(), |img| {
+                overrides::scale_mode(img).swaybg().to_string()
+            });
+
+            // Cleanup old instances to prevent resource leaks/stacking
+            let _ = Command::new("pkill")
+                .args(["-x", "swaybg"])
+                .status()
+                .await
+                .context("oneshot: pkill -x swaybg")
+                .map_err(Error::Render)?;
+
+            Command::new("swaybg")
+                .args(&args)
+                .spawn()
+                .context("oneshot: spawn swaybg")
+                .map_err(Error::Render)?;
+        }
+
+        RendererType::Awww => {
+            let awww_bin = awww::detect_awww_binary().await;
+            awww::ensure_awww_daemon(&awww_bin)
+                .await
+                .map_err(Error::Render)?;
+            for (monitor, img) in rotation.assignments() {
+                Command::new(&awww_bin)
+                    .args(awww::build_awww_args(config, monitor, img))
+                    .status()
+                    .await
+                    .with_context(|| format!("oneshot: awww img -o {monitor}"))
+                    .map_err(Error::Render)?;
             }
         }
+
+        RendererType::Feh => feh::apply(rotation)
+            .await
+            .context("oneshot: feh")
+            .map_err(Error::Render)?,
+
+        RendererType::Xwallpaper => xwallpaper::apply(rotation)
+            .await
+            .context("oneshot: xwallpaper")
+            .map_err(Error::Render)?,
+
+        RendererType::Gnome => {
+            GnomeBackend::default()
+                .set_wallpaper(rotation.assignments())
+                .await?;
+        }
+
+        RendererType::Kde => {
+            KdeBackend::default()
+                .set_wallpaper(rotation.assignments())
+                .await?;
+        }
     }
+
+    Ok(())
 }
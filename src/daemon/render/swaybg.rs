@@ -1,24 +1,23 @@
-use crate::wallpaper::WallpaperCache;
+use crate::overrides;
+use crate::rotation::Rotation;
 use anyhow::Context;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tokio::process::{Child, Command};
 
 /// Constructs the command-line arguments for `swaybg`.
 ///
-/// It maps each monitor to a randomly selected wallpaper from the cache.
+/// Each `(monitor, image)` assignment becomes an `-o <monitor> -m <mode> -i <image>` group.
+/// Assignments whose image cannot be canonicalized are skipped.
 ///
 /// # Arguments
-/// * `monitors` - Slice of monitor names to apply wallpapers to.
-/// * `pick_random` - Closure that returns a path to a random image.
-/// * `mode` - Closure that returns the scaling mode (e.g., "fill").
-fn build_swaybg_args<F, M>(monitors: &[String], pick_random: F, mode: M) -> Vec<String>
+/// * `assignments` - The monitor/image pairs decided for this rotation.
+/// * `mode` - Closure that returns the scaling mode for an image (e.g., "fill").
+pub fn build_swaybg_args<M>(assignments: &[(String, PathBuf)], mode: M) -> Vec<String>
 where
-    F: Fn() -> PathBuf,
-    M: Fn() -> String,
+    M: Fn(&Path) -> String,
 {
     let mut args = Vec::new();
-    for monitor in monitors {
-        let img = pick_random();
+    for (monitor, img) in assignments {
         let Ok(abs_path) = img.canonicalize() else {
             continue;
         };
@@ -26,7 +25,7 @@ where
         args.push("-o".to_string());
         args.push(monitor.clone());
         args.push("-m".to_string());
-        args.push(mode());
+        args.push(mode(&abs_path));
         args.push("-i".to_string());
         args.push(abs_path.to_string_lossy().to_string());
     }
@@ -41,13 +40,10 @@ where
 ///
 /// # Errors
 /// Returns an error if the command fails to spawn.
-pub async fn apply(
-    cache: &WallpaperCache,
-    monitors: &[String],
-    current: &mut Option<Child>,
-) -> anyhow::Result<()> {
-    let pick_random = || cache.pick_random().to_path_buf();
-    let args = build_swaybg_args(monitors, pick_random, || "fill".to_string());
+pub async fn apply(rotation: &Rotation, current: &mut Option<Child>) -> anyhow::Result<()> {
+    let args = build_swaybg_args(rotation.assignments(), |img| {
+        overrides::scale_mode(img).swaybg().to_string()
+    });
 
     if args.is_empty() {
         return Ok(());
@@ -70,3 +66,63 @@ pub async fn apply(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{StubDir, touch};
+
+    #[test]
+    fn builds_one_group_per_monitor() {
+        let dir = StubDir::new();
+        let (a, b) = (dir.path("a.png"), dir.path("b.jpg"));
+        touch(&a);
+        touch(&b);
+        std::fs::write(dir.path("b.jpg.randpaper.toml"), "mode = \"fit\"\n").expect("sidecar");
+
+        let args = build_swaybg_args(
+            &[
+                ("DP-1".to_string(), a.clone()),
+                ("HDMI-A-1".to_string(), b.clone()),
+            ],
+            |img| overrides::scale_mode(img).swaybg().to_string(),
+        );
+
+        let abs = |p: &PathBuf| p.canonicalize().expect("path").display().to_string();
+        assert_eq!(
+            args,
+            [
+                "-o",
+                "DP-1",
+                "-m",
+                "fill",
+                "-i",
+                &abs(&a), //
+                "-o",
+                "HDMI-A-1",
+                "-m",
+                "fit",
+                "-i",
+                &abs(&b),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_missing_images() {
+        let dir = StubDir::new();
+        let a = dir.path("a.png");
+        touch(&a);
+
+        let args = build_swaybg_args(
+            &[
+                ("DP-1".to_string(), dir.path("gone.png")),
+                ("DP-2".to_string(), a),
+            ],
+            |_| "fill".to_string(),
+        );
+        assert_eq!(args[..2], ["-o", "DP-2"]);
+        assert_eq!(args.len(), 6);
+        assert!(build_swaybg_args(&[], |_| "fill".to_string()).is_empty());
+    }
+}
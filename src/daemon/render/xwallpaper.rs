@@ -0,0 +1,85 @@
+use crate::overrides;
+use crate::rotation::Rotation;
+use anyhow::{Context, bail};
+use std::path::PathBuf;
+use tokio::process::Command;
+
+/// Constructs the command-line arguments for `xwallpaper`.
+///
+/// Each `(monitor, image)` assignment becomes an `--output <monitor> --<mode> <image>` group.
+/// Assignments whose image cannot be canonicalized are skipped.
+pub fn build_xwallpaper_args(assignments: &[(String, PathBuf)]) -> Vec<String> {
+    let mut args = Vec::new();
+    for (monitor, img) in assignments {
+        let Ok(abs_path) = img.canonicalize() else {
+            continue;
+        };
+
+        args.push("--output".to_string());
+        args.push(monitor.clone());
+        args.push(overrides::scale_mode(&abs_path).xwallpaper().to_string());
+        args.push(abs_path.to_string_lossy().to_string());
+    }
+    args
+}
+
+/// Applies wallpapers per `xrandr` output with `xwallpaper`.
+///
+/// # Errors
+/// Returns an error if `xwallpaper` cannot be run or fails.
+pub async fn apply(rotation: &Rotation) -> anyhow::Result<()> {
+    let args = build_xwallpaper_args(rotation.assignments());
+    if args.is_empty() {
+        return Ok(());
+    }
+
+    let out = Command::new("xwallpaper")
+        .args(&args)
+        .output()
+        .await
+        .context("failed to run 'xwallpaper'. Is it installed and in your PATH?")?;
+    if !out.status.success() {
+        bail!(
+            "xwallpaper failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{StubDir, touch};
+
+    #[test]
+    fn builds_one_output_group_per_monitor() {
+        let dir = StubDir::new();
+        let (a, b) = (dir.path("a.png"), dir.path("b.jpg"));
+        touch(&a);
+        touch(&b);
+        std::fs::write(dir.path("b.jpg.randpaper.toml"), "mode = \"center\"\n").expect("sidecar");
+
+        let args = build_xwallpaper_args(&[
+            ("DP-1".to_string(), a.clone()),
+            ("DP-2".to_string(), b.clone()),
+            ("DP-3".to_string(), dir.path("gone.png")),
+        ]);
+
+        let abs = |p: &PathBuf| p.canonicalize().expect("path").display().to_string();
+        assert_eq!(
+            args,
+            [
+                "--output",
+                "DP-1",
+                "--zoom",
+                &abs(&a), //
+                "--output",
+                "DP-2",
+                "--center",
+                &abs(&b),
+            ]
+        );
+        assert!(build_xwallpaper_args(&[]).is_empty());
+    }
+}
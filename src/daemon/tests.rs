@@ -0,0 +1,199 @@
+use super::run_with_renderer;
+use crate::backends::mock::MockBackend;
+use crate::cli::{Config, Targets, ThemeFollow};
+use crate::error::{Error, Result};
+use crate::rotation::Rotation;
+use crate::test_util::StubDir;
+use crate::traits::Render;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant, sleep, timeout};
+
+type Assignments = Vec<(String, PathBuf)>;
+
+/// Hands every rotation it is asked to draw back to the test.
+struct RecordingRenderer {
+    rotations: mpsc::UnboundedSender<Assignments>,
+    fail: bool,
+}
+
+#[async_trait]
+impl Render for RecordingRenderer {
+    async fn apply(&mut self, _config: &Config, rotation: &Rotation) -> Result<()> {
+        if self.fail {
+            return Err(Error::Render(anyhow!("renderer exploded")));
+        }
+        let _ = self.rotations.send(rotation.assignments().to_vec());
+        Ok(())
+    }
+}
+
+/// A wallpaper directory with a few small, differently colored images.
+fn wallpapers() -> StubDir {
+    let dir = StubDir::new();
+    for (name, rgb) in [
+        ("red.png", [200, 40, 40]),
+        ("green.png", [40, 160, 60]),
+        ("blue.png", [30, 60, 200]),
+    ] {
+        image::RgbImage::from_pixel(8, 8, image::Rgb(rgb))
+            .save(dir.path(name))
+            .expect("write wallpaper");
+    }
+    dir
+}
+
+/// A daemon config that rotates every `time` and writes no theme targets; only
+/// `theme.json` and the fallback `waybar.css` land in `walls/themes`.
+fn config(walls: &StubDir, time: &str) -> Config {
+    let mut config = Config {
+        wallpaper_dir: walls.path(""),
+        time: Some(time.to_string()),
+        daemon: true,
+        ..Config::default()
+    };
+    config.theme.dir = Some(walls.path("themes"));
+    config.theme.targets = Targets::none();
+    config
+}
+
+struct Daemon {
+    task: JoinHandle<Result<()>>,
+    rotations: mpsc::UnboundedReceiver<Assignments>,
+}
+
+impl Daemon {
+    fn start(config: Config, backend: MockBackend, fail: bool) -> Self {
+        let (tx, rotations) = mpsc::unbounded_channel();
+        let renderer = RecordingRenderer {
+            rotations: tx,
+            fail,
+        };
+        let task = tokio::spawn(run_with_renderer(
+            config,
+            backend,
+            renderer,
+            Duration::from_millis(20),
+        ));
+        Self { task, rotations }
+    }
+
+    /// Waits for the next rotation the daemon draws.
+    async fn next(&mut self) -> Assignments {
+        timeout(Duration::from_secs(10), self.rotations.recv())
+            .await
+            .expect("daemon should rotate")
+            .expect("daemon stopped")
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn monitors(rotation: &Assignments) -> Vec<&str> {
+    rotation.iter().map(|(m, _)| m.as_str()).collect()
+}
+
+fn assert_from(rotation: &Assignments, walls: &Path) {
+    let walls = walls.canonicalize().expect("wallpaper dir");
+    for (monitor, img) in rotation {
+        assert!(img.starts_with(&walls), "{monitor} got {}", img.display());
+    }
+}
+
+#[tokio::test]
+async fn rotates_every_monitor_on_the_timer() {
+    let walls = wallpapers();
+    let backend = MockBackend::new(&["DP-1", "HDMI-A-1"]);
+    let mut daemon = Daemon::start(config(&walls, "100ms"), backend.clone(), false);
+
+    for _ in 0..3 {
+        let rotation = daemon.next().await;
+        assert_eq!(monitors(&rotation), ["DP-1", "HDMI-A-1"]);
+        assert_from(&rotation, &walls.path(""));
+    }
+    assert!(backend.queries() >= 3);
+}
+
+#[tokio::test]
+async fn retries_until_monitors_are_available() {
+    let walls = wallpapers();
+    let backend = MockBackend::new(&["eDP-1"]);
+    backend.fail_next(3);
+    let mut daemon = Daemon::start(config(&walls, "1h"), backend.clone(), false);
+
+    assert_eq!(monitors(&daemon.next().await), ["eDP-1"]);
+    assert_eq!(backend.queries(), 4);
+}
+
+#[tokio::test]
+async fn rotates_right_away_when_outputs_change() {
+    let walls = wallpapers();
+    let backend = MockBackend::new(&["DP-1"]);
+    let mut daemon = Daemon::start(config(&walls, "1h"), backend.clone(), false);
+    assert_eq!(monitors(&daemon.next().await), ["DP-1"]);
+
+    // The rotation planned for DP-1 alone must not be reused
+    backend.plug(&["DP-1", "DP-2"]);
+    let rotation = daemon.next().await;
+    assert_eq!(monitors(&rotation), ["DP-1", "DP-2"]);
+    assert_from(&rotation, &walls.path(""));
+
+    backend.plug(&["DP-2"]);
+    assert_eq!(monitors(&daemon.next().await), ["DP-2"]);
+}
+
+#[tokio::test]
+async fn pushes_border_colors_after_each_rotation() {
+    let walls = wallpapers();
+    let backend = MockBackend::new(&["DP-1", "DP-2"]);
+    backend.focus("DP-2");
+    let mut config = config(&walls, "1h");
+    config.theme.borders = true;
+    config.theme.follow = ThemeFollow::Focused;
+    let mut daemon = Daemon::start(config, backend.clone(), false);
+    daemon.next().await;
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while backend.borders().is_empty() {
+        assert!(Instant::now() < deadline, "border colors were never sent");
+        sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(backend.borders().len(), 1);
+}
+
+#[tokio::test]
+async fn stops_when_the_renderer_fails() {
+    let walls = wallpapers();
+    let mut daemon = Daemon::start(config(&walls, "1h"), MockBackend::new(&["DP-1"]), true);
+
+    let result = timeout(Duration::from_secs(10), &mut daemon.task)
+        .await
+        .expect("daemon should stop")
+        .expect("daemon task");
+    let err = result.expect_err("should fail");
+    assert!(matches!(err, Error::Render(_)), "{err:?}");
+    assert!(err.to_string().contains("renderer exploded"), "{err}");
+}
+
+#[tokio::test]
+async fn refuses_to_start_without_a_rotation_time() {
+    let walls = wallpapers();
+    let mut config = config(&walls, "1h");
+    config.time = None;
+    let mut daemon = Daemon::start(config, MockBackend::new(&["DP-1"]), false);
+
+    let result = timeout(Duration::from_secs(10), &mut daemon.task)
+        .await
+        .expect("daemon should stop")
+        .expect("daemon task");
+    let err = result.expect_err("should fail");
+    assert!(matches!(err, Error::Config(_)), "{err:?}");
+    assert!(err.to_string().contains("requires `time`"), "{err}");
+}
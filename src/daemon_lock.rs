@@ -34,6 +34,11 @@ fn session_key() -> String {
         return truncate_ascii(format!("hypr-{}", sanitize_component(&sig)), 80);
     }
 
+    if let Ok(sock) = env::var("NIRI_SOCKET") {
+        let base = sway_sock_basename(&sock);
+        return truncate_ascii(format!("niri-{}", sanitize_component(base)), 80);
+    }
+
     if let Ok(sock) = env::var("SWAYSOCK") {
         let base = sway_sock_basename(&sock);
         return truncate_ascii(format!("sway-{}", sanitize_component(base)), 80);
@@ -0,0 +1,36 @@
+//! The error type returned by randpaper's public API.
+//!
+//! Internals build `anyhow` errors with context chains; the public API tags
+//! them with the stage that failed, so callers can react to e.g. an
+//! unreachable compositor differently from a broken theme.
+use std::path::PathBuf;
+
+/// Errors returned by the library.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// The configuration could not be loaded or holds an invalid value.
+    #[error("{0:#}")]
+    Config(anyhow::Error),
+    /// The wallpaper directory could not be walked.
+    #[error("failed to read the wallpaper directory")]
+    Scan(#[from] walkdir::Error),
+    /// The wallpaper directory holds no supported images.
+    #[error("no supported images found in {}", .0.display())]
+    NoWallpapers(PathBuf),
+    /// The compositor could not be found, reached or queried.
+    #[error("{0:#}")]
+    Backend(anyhow::Error),
+    /// The wallpaper tool failed to start or rejected the images.
+    #[error("{0:#}")]
+    Render(anyhow::Error),
+    /// A theme could not be built, written or reloaded.
+    #[error("{0:#}")]
+    Theme(anyhow::Error),
+    /// The daemon could not listen for its skip signal.
+    #[error("failed to listen for SIGUSR1")]
+    Signal(#[source] std::io::Error),
+}
+
+/// `Result` with [`Error`] as the default error type.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
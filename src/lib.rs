@@ -0,0 +1,39 @@
+//! Per-monitor wallpaper rotation with theme syncing.
+//!
+//! The `randpaper` binary is a thin client over this crate; session managers
+//! can embed the same pieces:
+//!
+//! - [`WallpaperCache`] scans a directory and picks images.
+//! - A [`Backend`] lists the compositor's monitors ([`backends`] has one per
+//!   supported compositor), and a [`Rotation`] assigns an image to each.
+//! - A [`Render`] implementation such as [`daemon::Renderer`] puts the images
+//!   on screen.
+//! - [`theme`] extracts palettes and writes the theme targets.
+//!
+//! [`daemon::run_loop`] and [`daemon::run_once`] wire these together the way
+//! the binary does.
+//!
+//! # Features
+//!
+//! - `extract` (default): palette extraction from images via `image` and
+//!   `color-thief`. Without it only `scheme:` theme sources work.
+//! - `swayipc` (default): native Sway IPC. Without it the Sway backend only
+//!   uses `swaymsg`.
+#![allow(clippy::multiple_crate_versions)]
+pub mod backends;
+pub mod cli;
+pub mod daemon;
+pub mod error;
+pub mod overrides;
+pub mod rotation;
+#[cfg(test)]
+pub(crate) mod test_util;
+pub mod theme;
+pub mod traits;
+pub mod wallpaper;
+
+pub use cli::Config;
+pub use error::{Error, Result};
+pub use rotation::Rotation;
+pub use traits::{Backend, Render};
+pub use wallpaper::WallpaperCache;
@@ -1,117 +1,81 @@
 #![allow(clippy::multiple_crate_versions)]
-mod backends;
-mod cli;
-mod daemon;
 mod daemon_lock;
-mod theme;
-mod traits;
-mod wallpaper;
 
-use crate::backends::hyprland::HyprlandBackend;
-use crate::backends::sway::SwayBackend;
-use crate::traits::Backend;
-use crate::wallpaper::WallpaperCache;
-use anyhow::Context;
-use cli::{BackendType, Config, RendererType};
-use tokio::process::Command;
+use clap::Parser;
+use randpaper::backends::filter::OutputFilter;
+use randpaper::backends::gnome::GnomeBackend;
+use randpaper::backends::hyprland::HyprlandBackend;
+use randpaper::backends::kde::KdeBackend;
+use randpaper::backends::niri::NiriBackend;
+use randpaper::backends::sway::SwayBackend;
+use randpaper::backends::wayland::WaylandBackend;
+use randpaper::backends::x11::X11Backend;
+use randpaper::cli::{BackendType, Cli, Commands, Config, RendererType};
+use randpaper::{backends, daemon, theme};
 
-/// Executes a single wallpaper and theme update.
-///
-/// This mode is triggered when the user does not provide a `--time` interval.
-/// It detects monitors via the chosen backend, picks a random wallpaper,
-/// updates the system themes, and invokes the selected renderer.
-async fn oneshot_mode(config: &Config) -> anyhow::Result<()> {
-    log::info!("One-shot mode: picking wallpaper once and exiting");
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    env_logger::init();
 
-    // Initialize the wallpaper cache from the provided directory
-    let cache = WallpaperCache::new(&config.wallpaper_dir)?;
+    let mut cli = Cli::parse();
+    let command = cli.command.take();
+    let mut config = Config::new(cli)?;
+
+    // Subcommands run standalone and never touch the renderer or theme files
+    if let Some(Commands::Palette {
+        image,
+        format,
+        output,
+    }) = command
+    {
+        return Ok(theme::palette::run(
+            &image,
+            format,
+            output,
+            &config.theme.tuning,
+        )?);
+    }
 
-    // 1. Identify active monitors based on the user-selected backend (Hyprland or Sway)
-    let monitors = match config.backend {
-        BackendType::Hyprland => HyprlandBackend.get_active_monitors().await?,
-        BackendType::Sway => {
-            let backend = SwayBackend {
-                outputs_override: config.outputs.clone(),
-            };
-            backend.get_active_monitors().await?
-        }
-    };
+    theme::ensure_theme_exists(&config.theme)?;
+    config.backend = backends::detect::resolve(config.backend)?;
+    if let Some(renderer) = config.backend.renderer() {
+        config.renderer = renderer;
+    } else if config.backend == BackendType::X11 && !config.renderer.is_x11() {
+        log::info!(
+            "{:?} renderer needs Wayland; using feh on X11",
+            config.renderer
+        );
+        config.renderer = RendererType::Feh;
+    }
 
-    // 2. Pick wallpaper and generate the theme files (Waybar, Terminals)
-    let img = cache.pick_random();
-    theme::update_theme_file(img)?;
+    let outputs = OutputFilter::new(&config.outputs)?;
+    if !config.outputs.is_empty()
+        && !matches!(config.backend, BackendType::Sway | BackendType::Hyprland)
+    {
+        log::warn!(
+            "outputs is only applied on Sway and Hyprland; ignoring it on {:?}",
+            config.backend
+        );
+    }
 
-    // 3. Apply the wallpaper using the selected renderer (swaybg or awww)
-    match config.renderer {
-        RendererType::Swaybg => {
-            let mut args = Vec::new();
-            for monitor in &monitors {
-                let img = cache.pick_random();
-                let abs_path = img.canonicalize()?;
-                args.extend_from_slice(&[
-                    "-o".to_string(),
-                    monitor.clone(),
-                    "-m".to_string(),
-                    "fill".to_string(),
-                    "-i".to_string(),
-                    abs_path.to_string_lossy().to_string(),
-                ]);
+    if !config.daemon {
+        match config.backend {
+            BackendType::Auto => unreachable!("backend resolved above"),
+            BackendType::Gnome => daemon::run_once(&config, &GnomeBackend::default()).await?,
+            BackendType::Hyprland => {
+                daemon::run_once(&config, &HyprlandBackend::from_env()?.with_outputs(outputs))
+                    .await?;
             }
-
-            // Cleanup old instances to prevent resource leaks/stacking
-            let _ = Command::new("pkill")
-                .args(["-x", "swaybg"])
-                .status()
-                .await
-                .context("oneshot: pkill -x swaybg")?;
-
-            // Start new swaybg
-            // Command::new("swaybg").args(&args).spawn()?;
-            Command::new("swaybg")
-                .args(&args)
-                .spawn()
-                .context("oneshot: spawn swaybg")?;
-        }
-
-        RendererType::Awww => {
-            let awww_bin = daemon::detect_awww_binary().await; // Use from daemon module
-            daemon::ensure_awww_daemon(&awww_bin).await?;
-            let step = config.transition_step.to_string();
-            let fps = config.transition_fps.to_string();
-
-            for monitor in &monitors {
-                let img = cache.pick_random();
-                Command::new(&awww_bin)
-                    .arg("img")
-                    .arg(img)
-                    .arg("-o")
-                    .arg(monitor)
-                    .arg("--transition-type")
-                    .arg(&config.transition_type)
-                    .arg("--transition-step")
-                    .arg(&step)
-                    .arg("--transition-fps")
-                    .arg(&fps)
-                    .status()
-                    .await
-                    .with_context(|| format!("oneshot: awww img -o {monitor}"))?;
+            BackendType::Kde => daemon::run_once(&config, &KdeBackend::default()).await?,
+            BackendType::Niri => daemon::run_once(&config, &NiriBackend::from_env()?).await?,
+            BackendType::Sway => {
+                let backend = SwayBackend { outputs };
+                daemon::run_once(&config, &backend).await?;
             }
+            BackendType::Wayland => daemon::run_once(&config, &WaylandBackend).await?,
+            BackendType::X11 => daemon::run_once(&config, &X11Backend::default()).await?,
         }
-    }
-
-    log::info!("Wallpaper and theme updated. Exiting.");
-    Ok(())
-}
-
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    env_logger::init();
-
-    let config = Config::new()?;
-    crate::theme::ensure_theme_exists()?;
-
-    if !config.daemon {
-        return oneshot_mode(&config).await;
+        return Ok(());
     }
 
     // --daemon mode
@@ -126,16 +90,39 @@ async fn main() -> anyhow::Result<()> {
     };
 
     match config.backend {
+        BackendType::Auto => unreachable!("backend resolved above"),
+        BackendType::Gnome => {
+            log::info!("Using GNOME backend");
+            Box::pin(daemon::run_loop(config, GnomeBackend::default())).await?;
+        }
         BackendType::Hyprland => {
             log::info!("Using Hyprland backend");
-            daemon::run_loop(config, HyprlandBackend).await?;
+            Box::pin(daemon::run_loop(
+                config,
+                HyprlandBackend::from_env()?.with_outputs(outputs),
+            ))
+            .await?;
+        }
+        BackendType::Kde => {
+            log::info!("Using KDE Plasma backend");
+            Box::pin(daemon::run_loop(config, KdeBackend::default())).await?;
+        }
+        BackendType::Niri => {
+            log::info!("Using niri backend");
+            Box::pin(daemon::run_loop(config, NiriBackend::from_env()?)).await?;
         }
         BackendType::Sway => {
             log::info!("Using Sway backend");
-            let backend = SwayBackend {
-                outputs_override: config.outputs.clone(),
-            };
-            daemon::run_loop(config, backend).await?;
+            let backend = SwayBackend { outputs };
+            Box::pin(daemon::run_loop(config, backend)).await?;
+        }
+        BackendType::Wayland => {
+            log::info!("Using generic Wayland backend");
+            Box::pin(daemon::run_loop(config, WaylandBackend)).await?;
+        }
+        BackendType::X11 => {
+            log::info!("Using X11 backend");
+            Box::pin(daemon::run_loop(config, X11Backend::default())).await?;
         }
     }
 
@@ -0,0 +1,360 @@
+use crate::theme::{Rgb, Roles, expand_tilde};
+use figment::Figment;
+use figment::providers::{Format, Toml};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Appended to an image's file name to find its sidecar (`city.jpg.randpaper.toml`).
+const SIDECAR_SUFFIX: &str = ".randpaper.toml";
+
+/// How a wallpaper is scaled onto its monitor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScaleMode {
+    #[default]
+    Fill,
+    Fit,
+    Stretch,
+    Center,
+    Tile,
+}
+
+impl ScaleMode {
+    /// The matching `swaybg --mode` value.
+    #[must_use]
+    pub const fn swaybg(self) -> &'static str {
+        match self {
+            Self::Fill => "fill",
+            Self::Fit => "fit",
+            Self::Stretch => "stretch",
+            Self::Center => "center",
+            Self::Tile => "tile",
+        }
+    }
+
+    /// The matching `awww img --resize` value, if awww has one.
+    #[must_use]
+    pub const fn awww_resize(self) -> Option<&'static str> {
+        match self {
+            Self::Fill => Some("crop"),
+            Self::Fit => Some("fit"),
+            Self::Center => Some("no"),
+            Self::Stretch | Self::Tile => None,
+        }
+    }
+
+    /// The matching `feh --bg-*` flag.
+    #[must_use]
+    pub const fn feh(self) -> &'static str {
+        match self {
+            Self::Fill => "--bg-fill",
+            Self::Fit => "--bg-max",
+            Self::Stretch => "--bg-scale",
+            Self::Center => "--bg-center",
+            Self::Tile => "--bg-tile",
+        }
+    }
+
+    /// The matching `xwallpaper` placement flag.
+    #[must_use]
+    pub const fn xwallpaper(self) -> &'static str {
+        match self {
+            Self::Fill => "--zoom",
+            Self::Fit => "--maximize",
+            Self::Stretch => "--stretch",
+            Self::Center => "--center",
+            Self::Tile => "--tile",
+        }
+    }
+
+    /// The matching GNOME `picture-options` value.
+    #[must_use]
+    pub const fn gnome(self) -> &'static str {
+        match self {
+            Self::Fill => "zoom",
+            Self::Fit => "scaled",
+            Self::Stretch => "stretched",
+            Self::Center => "centered",
+            Self::Tile => "wallpaper",
+        }
+    }
+
+    /// The matching Plasma `org.kde.image` `FillMode` value.
+    #[must_use]
+    pub const fn plasma_fill_mode(self) -> u8 {
+        match self {
+            Self::Stretch => 0,
+            Self::Fit => 1,
+            Self::Fill => 2,
+            Self::Tile => 3,
+            Self::Center => 6,
+        }
+    }
+}
+
+/// Role colors pinned for one image. Unset roles keep the picked color.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RoleOverrides {
+    pub bg: Option<Rgb>,
+    pub fg: Option<Rgb>,
+    pub accent: Option<Rgb>,
+    pub warn: Option<Rgb>,
+    pub ok: Option<Rgb>,
+}
+
+impl RoleOverrides {
+    /// Replaces every pinned role in `roles`.
+    pub fn apply(&self, roles: &mut Roles) {
+        let pinned = [
+            (self.bg, &mut roles.bg),
+            (self.fg, &mut roles.fg),
+            (self.accent, &mut roles.accent),
+            (self.warn, &mut roles.warn),
+            (self.ok, &mut roles.ok),
+        ];
+        for (pin, role) in pinned {
+            if let Some(color) = pin {
+                *role = color;
+            }
+        }
+    }
+}
+
+/// Per-image settings read from a sidecar file or the central `overrides.toml`.
+///
+/// ```toml
+/// mode = "fit"
+/// palette = ["#1d2021", "#cc241d", ...]  # replaces the extracted palette
+///
+/// [roles]
+/// accent = "#d79921"
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ImageOverride {
+    /// Scaling mode for the renderer (`fill` if unset).
+    pub mode: Option<ScaleMode>,
+    /// A palette used instead of the extracted one; roles are picked from it.
+    pub palette: Option<Vec<Rgb>>,
+    /// Roles pinned after the palette is settled.
+    pub roles: RoleOverrides,
+}
+
+/// The sidecar path for `image` (`<image>.randpaper.toml`).
+fn sidecar_path(image: &Path) -> PathBuf {
+    let mut name = image.as_os_str().to_owned();
+    name.push(SIDECAR_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// The central overrides file, `~/.config/randpaper/overrides.toml`.
+fn central_path() -> Option<PathBuf> {
+    crate::theme::config_dir()
+        .ok()
+        .map(|d| d.join("randpaper/overrides.toml"))
+}
+
+/// Finds the entry for `image` in the central file.
+///
+/// Keys are either a full path (`~/` is expanded) or a bare file name; a full
+/// path match wins over a file name match.
+fn central_entry(
+    mut entries: BTreeMap<String, ImageOverride>,
+    image: &Path,
+) -> Option<ImageOverride> {
+    let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+    let image = canonical(image);
+
+    let by_path = entries
+        .keys()
+        .find(|key| key.contains('/') && canonical(&expand_tilde(Path::new(key))) == image)
+        .cloned();
+    let key = by_path.or_else(|| {
+        let name = image.file_name()?.to_str()?;
+        entries.contains_key(name).then(|| name.to_string())
+    })?;
+    entries.remove(&key)
+}
+
+/// Reads the sidecar of `image`, falling back to its entry in `central`.
+fn load(image: &Path, central: Option<&Path>) -> anyhow::Result<Option<ImageOverride>> {
+    let sidecar = sidecar_path(image);
+    if sidecar.is_file() {
+        return Ok(Some(Figment::from(Toml::file(&sidecar)).extract()?));
+    }
+
+    match central {
+        Some(central) if central.is_file() => {
+            let entries = Figment::from(Toml::file(central)).extract()?;
+            Ok(central_entry(entries, image))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Looks up the overrides for `image`: its sidecar if present, otherwise its
+/// entry in the central file.
+///
+/// A broken override file is logged and ignored so it never blocks a rotation.
+#[must_use]
+pub fn for_image(image: &Path) -> ImageOverride {
+    load(image, central_path().as_deref())
+        .unwrap_or_else(|e| {
+            log::warn!("Ignoring overrides for {}: {e:#}", image.display());
+            None
+        })
+        .unwrap_or_default()
+}
+
+/// The scaling mode for `image`, defaulting to [`ScaleMode::Fill`].
+#[must_use]
+pub fn scale_mode(image: &Path) -> ScaleMode {
+    for_image(image).mode.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{StubDir, touch};
+
+    fn hex(s: &str) -> Rgb {
+        Rgb::from_hex(s).expect("valid hex")
+    }
+
+    #[test]
+    fn a_sidecar_wins_over_the_central_file() {
+        let dir = StubDir::new();
+        let image = dir.path("city.jpg");
+        touch(&image);
+        let central = dir.path("overrides.toml");
+        std::fs::write(&central, "[\"city.jpg\"]\nmode = \"tile\"\n").expect("write central");
+
+        let from_central = load(&image, Some(&central)).expect("load").expect("entry");
+        assert_eq!(from_central.mode, Some(ScaleMode::Tile));
+
+        std::fs::write(
+            dir.path("city.jpg.randpaper.toml"),
+            "mode = \"fit\"\n[roles]\naccent = \"#d79921\"\n",
+        )
+        .expect("write sidecar");
+        let from_sidecar = load(&image, Some(&central)).expect("load").expect("entry");
+        assert_eq!(from_sidecar.mode, Some(ScaleMode::Fit));
+        assert_eq!(from_sidecar.roles.accent, Some(hex("#d79921")));
+    }
+
+    #[test]
+    fn central_paths_win_over_file_names() {
+        let dir = StubDir::new();
+        let image = dir.path("city.jpg");
+        touch(&image);
+        let central = dir.path("overrides.toml");
+        std::fs::write(
+            &central,
+            format!(
+                "[\"city.jpg\"]\nmode = \"tile\"\n\n[\"{}\"]\nmode = \"center\"\n",
+                image.display()
+            ),
+        )
+        .expect("write central");
+
+        let entry = load(&image, Some(&central)).expect("load").expect("entry");
+        assert_eq!(entry.mode, Some(ScaleMode::Center));
+        assert!(
+            load(&dir.path("other.jpg"), Some(&central))
+                .expect("load")
+                .is_none()
+        );
+        assert!(load(&image, None).expect("load").is_none());
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        let dir = StubDir::new();
+        let image = dir.path("city.jpg");
+        std::fs::write(dir.path("city.jpg.randpaper.toml"), "zoom = 2\n").expect("write sidecar");
+        assert!(load(&image, None).is_err());
+    }
+
+    #[test]
+    fn pinned_roles_replace_only_their_role() {
+        let mut roles = Roles {
+            bg: hex("#000000"),
+            fg: hex("#ffffff"),
+            accent: hex("#0000ff"),
+            warn: hex("#ff0000"),
+            ok: hex("#00ff00"),
+        };
+        let pins = RoleOverrides {
+            accent: Some(hex("#d79921")),
+            ..RoleOverrides::default()
+        };
+        pins.apply(&mut roles);
+        assert_eq!(roles.accent, hex("#d79921"));
+        assert_eq!(roles.bg, hex("#000000"));
+        assert_eq!(roles.ok, hex("#00ff00"));
+    }
+
+    #[test]
+    fn scale_modes_map_onto_every_renderer() {
+        let table = [
+            (
+                ScaleMode::Fill,
+                "fill",
+                Some("crop"),
+                "--bg-fill",
+                "--zoom",
+                "zoom",
+                2,
+            ),
+            (
+                ScaleMode::Fit,
+                "fit",
+                Some("fit"),
+                "--bg-max",
+                "--maximize",
+                "scaled",
+                1,
+            ),
+            (
+                ScaleMode::Stretch,
+                "stretch",
+                None,
+                "--bg-scale",
+                "--stretch",
+                "stretched",
+                0,
+            ),
+            (
+                ScaleMode::Center,
+                "center",
+                Some("no"),
+                "--bg-center",
+                "--center",
+                "centered",
+                6,
+            ),
+            (
+                ScaleMode::Tile,
+                "tile",
+                None,
+                "--bg-tile",
+                "--tile",
+                "wallpaper",
+                3,
+            ),
+        ];
+        for (mode, swaybg, awww, feh, xwallpaper, gnome, plasma) in table {
+            let parsed: ScaleMode = serde_json::from_str(&format!("\"{swaybg}\"")).expect("mode");
+            assert_eq!(parsed, mode);
+            assert_eq!(mode.swaybg(), swaybg);
+            assert_eq!(mode.awww_resize(), awww);
+            assert_eq!(mode.feh(), feh);
+            assert_eq!(mode.xwallpaper(), xwallpaper);
+            assert_eq!(mode.gnome(), gnome);
+            assert_eq!(mode.plasma_fill_mode(), plasma);
+        }
+        assert_eq!(ScaleMode::default(), ScaleMode::Fill);
+    }
+}
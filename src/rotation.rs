@@ -0,0 +1,91 @@
+use crate::cli::ThemeFollow;
+use crate::traits::Backend;
+use crate::wallpaper::WallpaperCache;
+use std::path::PathBuf;
+
+/// The wallpapers chosen for one rotation, decided before anything is applied.
+///
+/// Both the renderer and the theme step read from the same `Rotation`, so the
+/// theme always comes from an image that is actually on screen.
+pub struct Rotation {
+    /// `(monitor, image)` pairs in the order the backend reported the monitors.
+    assignments: Vec<(String, PathBuf)>,
+}
+
+impl Rotation {
+    /// Assigns a random wallpaper from the cache to every monitor.
+    #[must_use]
+    pub fn pick(cache: &WallpaperCache, monitors: &[String]) -> Self {
+        let assignments = monitors
+            .iter()
+            .map(|m| (m.clone(), cache.pick_random().to_path_buf()))
+            .collect();
+        Self { assignments }
+    }
+
+    /// The `(monitor, image)` pairs to hand to a renderer.
+    #[must_use]
+    pub fn assignments(&self) -> &[(String, PathBuf)] {
+        &self.assignments
+    }
+
+    /// Returns true if this rotation was planned for exactly `monitors`.
+    #[must_use]
+    pub fn is_for(&self, monitors: &[String]) -> bool {
+        self.assignments.len() == monitors.len()
+            && self
+                .assignments
+                .iter()
+                .zip(monitors)
+                .all(|((a, _), b)| a == b)
+    }
+
+    /// Selects the image(s) whose palette should drive the theme.
+    ///
+    /// * `Primary` - the image on `primary` (or the first monitor if unset/absent).
+    /// * `Focused` - the image on `focused` (or the first monitor if unknown).
+    /// * `Blend` - every distinct image on screen.
+    ///
+    /// Returns an empty list if no monitors were assigned.
+    #[must_use]
+    pub fn theme_images(
+        &self,
+        follow: ThemeFollow,
+        primary: Option<&str>,
+        focused: Option<&str>,
+    ) -> Vec<PathBuf> {
+        let on = |name: Option<&str>| {
+            name.and_then(|n| self.assignments.iter().find(|(m, _)| m == n))
+                .or_else(|| self.assignments.first())
+                .map(|(_, img)| img.clone())
+        };
+
+        match follow {
+            ThemeFollow::Primary => on(primary).into_iter().collect(),
+            ThemeFollow::Focused => on(focused).into_iter().collect(),
+            ThemeFollow::Blend => {
+                let mut images: Vec<PathBuf> = Vec::new();
+                for (_, img) in &self.assignments {
+                    if !images.contains(img) {
+                        images.push(img.clone());
+                    }
+                }
+                images
+            }
+        }
+    }
+}
+
+/// Asks the backend for the focused monitor when the theme follows focus.
+///
+/// Returns `None` for other modes. Failures are logged and treated as
+/// "unknown", which makes [`Rotation::theme_images`] fall back to the first monitor.
+pub async fn focused_monitor<B: Backend>(follow: ThemeFollow, backend: &B) -> Option<String> {
+    if follow != ThemeFollow::Focused {
+        return None;
+    }
+    backend.get_focused_monitor().await.unwrap_or_else(|e| {
+        log::warn!("Failed to get focused monitor: {e:#}");
+        None
+    })
+}
@@ -0,0 +1,246 @@
+use super::oklab::Oklab;
+use super::{PALETTE_SIZE, Rgb, Roles, Theme};
+use crate::cli::Config;
+use crate::error::{Error, Result};
+use anyhow::{Context, ensure};
+use std::time::Duration;
+
+/// Hard ceiling on animation frames per second, whatever `theme.animation.fps` says.
+/// Every frame rewrites the animated targets and re-runs their reload actions.
+const MAX_FPS: u8 = 30;
+
+/// Full range of one color channel that an awww transition walks through.
+const CHANNEL_RANGE: f32 = 255.0;
+
+/// How a theme change is spread over time.
+#[derive(Clone, Copy, Debug)]
+pub struct Animation {
+    frames: u32,
+    interval: Duration,
+}
+
+impl Animation {
+    /// Resolves `[theme.animation]` into a frame count and spacing.
+    ///
+    /// Without an explicit `duration` the animation lasts roughly as long as an
+    /// awww transition: `255 / transition_step` frames at `transition_fps`.
+    /// Returns `None` if animation is disabled or too short to need a second frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`] if `theme.animation.duration` is not a valid duration.
+    pub fn from_config(config: &Config) -> Result<Option<Self>> {
+        let anim = &config.theme.animation;
+        if !anim.enabled {
+            return Ok(None);
+        }
+
+        let duration = if let Some(d) = &anim.duration {
+            parse_duration::parse(d).map_err(|e| {
+                Error::Config(anyhow::anyhow!("invalid theme.animation.duration: {e}"))
+            })?
+        } else {
+            let steps = (CHANNEL_RANGE / f32::from(config.transition_step.max(1))).ceil();
+            Duration::from_secs_f32(steps / f32::from(config.transition_fps.max(1)))
+        };
+
+        let fps = anim.fps.clamp(1, MAX_FPS);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let frames = (duration.as_secs_f32() * f32::from(fps)).ceil() as u32;
+        if frames < 2 {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            frames,
+            interval: duration / frames,
+        }))
+    }
+
+    /// Time between two frames.
+    #[must_use]
+    pub const fn interval(self) -> Duration {
+        self.interval
+    }
+
+    /// The intermediate themes from `from` to `to`, ending with `to` itself.
+    #[must_use]
+    pub fn frames(self, from: &Theme, to: &Theme) -> Vec<Theme> {
+        let mut frames: Vec<Theme> = (1..self.frames)
+            .map(|i| {
+                #[allow(clippy::cast_precision_loss)]
+                let t = i as f32 / self.frames as f32;
+                interpolate(from, to, t)
+            })
+            .collect();
+        frames.push(to.clone());
+        frames
+    }
+}
+
+fn mix(a: Rgb, b: Rgb, t: f32) -> Rgb {
+    Oklab::from(a).lerp(Oklab::from(b), t).into()
+}
+
+/// Blends two themes in `OKLab`. `wallpaper` is always the target's.
+fn interpolate(from: &Theme, to: &Theme, t: f32) -> Theme {
+    let (a, b) = (from.roles, to.roles);
+    Theme {
+        wallpaper: to.wallpaper.clone(),
+        roles: Roles {
+            bg: mix(a.bg, b.bg, t),
+            fg: mix(a.fg, b.fg, t),
+            accent: mix(a.accent, b.accent, t),
+            warn: mix(a.warn, b.warn, t),
+            ok: mix(a.ok, b.ok, t),
+        },
+        colors: from
+            .colors
+            .iter()
+            .zip(&to.colors)
+            .map(|(&a, &b)| mix(a, b, t))
+            .collect(),
+    }
+}
+
+/// Reads the previously applied theme back from `theme.json`.
+///
+/// # Errors
+///
+/// Returns an error if the file is missing, was not written by this version or
+/// does not hold a full 16-color palette (the terminal writers index all 16).
+pub fn previous_theme(theme_json: &std::path::Path) -> anyhow::Result<Theme> {
+    let raw = std::fs::read_to_string(theme_json)
+        .with_context(|| format!("failed to read {}", theme_json.display()))?;
+    let theme: Theme =
+        serde_json::from_str(&raw).with_context(|| format!("invalid {}", theme_json.display()))?;
+    ensure!(
+        theme.colors.len() == PALETTE_SIZE,
+        "{} has {} colors, expected {PALETTE_SIZE}",
+        theme_json.display(),
+        theme.colors.len()
+    );
+    Ok(theme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::theme_from_palette;
+    use std::path::Path;
+
+    fn config(duration: Option<&str>, fps: u8) -> Config {
+        let mut config = Config {
+            transition_step: 2,
+            transition_fps: 60,
+            ..Config::default()
+        };
+        config.theme.animation.enabled = true;
+        config.theme.animation.duration = duration.map(String::from);
+        config.theme.animation.fps = fps;
+        config
+    }
+
+    fn theme(hex: &str) -> Theme {
+        let color = Rgb::from_hex(hex).expect("valid hex");
+        theme_from_palette(Path::new("wall.png"), &[color])
+    }
+
+    #[test]
+    fn follows_the_awww_transition_by_default() {
+        // 255 / 2 = 128 steps at 60 fps is about 2.13 s, so 22 frames at 10 fps
+        let anim = Animation::from_config(&config(None, 10))
+            .expect("valid config")
+            .expect("animated");
+        assert_eq!(anim.frames, 22);
+        assert_eq!(anim.interval(), Duration::from_secs_f32(128.0 / 60.0) / 22);
+
+        let mut faster = config(None, 10);
+        faster.transition_fps = 120;
+        let quick = Animation::from_config(&faster)
+            .expect("valid config")
+            .expect("animated");
+        assert_eq!(quick.frames, 11);
+        assert_eq!(
+            quick.interval(),
+            Duration::from_secs_f32(128.0 / 120.0) / 11
+        );
+
+        let mut coarser = config(None, 10);
+        coarser.transition_step = 8;
+        let short = Animation::from_config(&coarser)
+            .expect("valid config")
+            .expect("animated");
+        assert_eq!(short.frames, 6);
+        assert_eq!(short.interval(), Duration::from_secs_f32(32.0 / 60.0) / 6);
+        assert_eq!(short.frames(&theme("#000000"), &theme("#ffffff")).len(), 6);
+
+        // The default 90-step transition is over before a second frame is due
+        let mut instant = config(None, 10);
+        instant.transition_step = 90;
+        instant.transition_fps = 30;
+        assert!(
+            Animation::from_config(&instant)
+                .expect("valid config")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn caps_fps_and_skips_single_frame_fades() {
+        let anim = Animation::from_config(&config(Some("500ms"), 120))
+            .expect("valid config")
+            .expect("animated");
+        assert_eq!(anim.frames, 15);
+
+        assert!(
+            Animation::from_config(&config(Some("50ms"), 10))
+                .expect("valid config")
+                .is_none()
+        );
+        assert!(
+            Animation::from_config(&Config::default())
+                .expect("valid config")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_durations() {
+        let err = Animation::from_config(&config(Some("soon"), 10)).expect_err("invalid");
+        assert!(matches!(err, Error::Config(_)), "{err:?}");
+    }
+
+    #[test]
+    fn rejects_a_truncated_previous_theme() {
+        let dir = crate::test_util::StubDir::new();
+        let path = dir.path("theme.json");
+        let mut short = theme("#336699");
+        std::fs::write(&path, serde_json::to_string(&short).expect("json")).expect("write");
+        assert!(previous_theme(&path).is_ok());
+
+        short.colors.truncate(5);
+        std::fs::write(&path, serde_json::to_string(&short).expect("json")).expect("write");
+        let err = previous_theme(&path).expect_err("too few colors");
+        assert!(err.to_string().contains("5 colors"), "{err:#}");
+    }
+
+    #[test]
+    fn frames_step_towards_the_new_theme() {
+        let anim = Animation::from_config(&config(Some("400ms"), 10))
+            .expect("valid config")
+            .expect("animated");
+        let (from, to) = (theme("#000000"), theme("#ffffff"));
+
+        let frames = anim.frames(&from, &to);
+        assert_eq!(frames.len(), 4);
+        let last = frames.last().expect("frames");
+        assert_eq!(last.roles, to.roles);
+        assert_eq!(last.colors, to.colors);
+
+        let first = &frames[0];
+        assert_eq!(first.colors.len(), to.colors.len());
+        assert_ne!(first.colors[0], from.colors[0]);
+        assert_ne!(first.colors[0], to.colors[0]);
+    }
+}
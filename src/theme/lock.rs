@@ -0,0 +1,140 @@
+use super::{Roles, Theme, atomic_write};
+#[cfg(feature = "extract")]
+use anyhow::Context;
+use std::fmt::Write;
+#[cfg(feature = "extract")]
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "extract")]
+use std::sync::Mutex;
+
+/// Longest edge of the lock image; blurring hides the lost detail and keeps this fast.
+#[cfg(feature = "extract")]
+const LOCK_IMAGE_PX: u32 = 1920;
+
+/// Gaussian blur strength applied to the lock image.
+#[cfg(feature = "extract")]
+const BLUR_SIGMA: f32 = 12.0;
+
+/// How much of the original brightness the lock image keeps.
+#[cfg(feature = "extract")]
+const DIM: f32 = 0.6;
+
+/// Wallpaper the current `lock.png` was made from, so animation frames and
+/// repeated targets don't redo the blur.
+#[cfg(feature = "extract")]
+static LOCK_SOURCE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Writes a blurred, dimmed copy of the wallpaper to `lock.png` in the theme
+/// directory and returns its path.
+#[cfg(feature = "extract")]
+fn lock_image(theme_dir: &Path, wallpaper: &Path) -> anyhow::Result<PathBuf> {
+    let out = theme_dir.join("lock.png");
+    let source = || {
+        LOCK_SOURCE
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    };
+    if source().as_deref() == Some(wallpaper) && out.exists() {
+        return Ok(out);
+    }
+
+    let mut img = image::open(wallpaper)
+        .with_context(|| format!("failed to open wallpaper: {}", wallpaper.display()))?;
+    if img.width().max(img.height()) > LOCK_IMAGE_PX {
+        img = img.resize(
+            LOCK_IMAGE_PX,
+            LOCK_IMAGE_PX,
+            image::imageops::FilterType::Triangle,
+        );
+    }
+    let img = img.fast_blur(BLUR_SIGMA);
+
+    let mut rgb = img.to_rgb8();
+    for px in rgb.pixels_mut() {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        px.0.iter_mut()
+            .for_each(|c| *c = (f32::from(*c) * DIM).round() as u8);
+    }
+
+    let mut png = Vec::new();
+    rgb.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .context("failed to encode lock image")?;
+    atomic_write(&out, &png)?;
+
+    *source() = Some(wallpaper.to_path_buf());
+    Ok(out)
+}
+
+/// Lock images need the `image` crate, which only the `extract` feature pulls in.
+#[cfg(not(feature = "extract"))]
+fn lock_image(_theme_dir: &Path, _wallpaper: &Path) -> anyhow::Result<PathBuf> {
+    anyhow::bail!("built without the `extract` feature; lock images are unavailable")
+}
+
+/// Writes a swaylock config (`swaylock -C <path>`) with the lock image and role colors.
+pub fn write_swaylock(path: &Path, theme: &Theme, theme_dir: &Path) -> anyhow::Result<()> {
+    let image = lock_image(theme_dir, &theme.wallpaper)?;
+    let Roles {
+        bg,
+        fg,
+        accent,
+        warn,
+        ok,
+    } = theme.roles;
+
+    let mut conf = String::from("# Auto-generated by randpaper\n");
+    let _ = writeln!(conf, "image={}", image.display());
+    let _ = writeln!(conf, "scaling=fill");
+    let _ = writeln!(conf, "color={}", bg.hex_rgba(0xff));
+    for (key, color, alpha) in [
+        ("inside-color", bg, 0xcc),
+        ("inside-clear-color", bg, 0xcc),
+        ("inside-ver-color", bg, 0xcc),
+        ("inside-wrong-color", bg, 0xcc),
+        ("ring-color", accent, 0xff),
+        ("ring-clear-color", fg, 0xff),
+        ("ring-ver-color", ok, 0xff),
+        ("ring-wrong-color", warn, 0xff),
+        ("key-hl-color", ok, 0xff),
+        ("bs-hl-color", warn, 0xff),
+        ("text-color", fg, 0xff),
+        ("text-clear-color", fg, 0xff),
+        ("text-ver-color", ok, 0xff),
+        ("text-wrong-color", warn, 0xff),
+        ("line-color", bg, 0x00),
+        ("separator-color", bg, 0x00),
+    ] {
+        let _ = writeln!(conf, "{key}={}", color.hex_rgba(alpha));
+    }
+    atomic_write(path, &conf)
+}
+
+/// Writes hyprlock variables (`source = <path>`): `$rp_wallpaper` plus one `$rp_*` per role.
+pub fn write_hyprlock(path: &Path, theme: &Theme, theme_dir: &Path) -> anyhow::Result<()> {
+    let image = lock_image(theme_dir, &theme.wallpaper)?;
+    let Roles {
+        bg,
+        fg,
+        accent,
+        warn,
+        ok,
+    } = theme.roles;
+
+    let mut conf = String::from("# Auto-generated by randpaper\n");
+    let _ = writeln!(conf, "$rp_wallpaper = {}", image.display());
+    for (name, color) in [
+        ("bg", bg),
+        ("fg", fg),
+        ("accent", accent),
+        ("warn", warn),
+        ("ok", ok),
+    ] {
+        let _ = writeln!(
+            conf,
+            "$rp_{name} = rgb({})",
+            color.hex().trim_start_matches('#')
+        );
+    }
+    atomic_write(path, &conf)
+}
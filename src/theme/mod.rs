@@ -0,0 +1,755 @@
+use crate::cli::{TargetConfig, ThemeConfig, ThemeSource, TuningConfig};
+use crate::error::{Error, Result};
+use crate::overrides::{self, ImageOverride};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{fs, thread};
+use tokio::task::JoinHandle;
+
+mod animate;
+mod lock;
+mod oklab;
+pub mod palette;
+mod popup;
+mod pywal;
+mod reload;
+mod scheme;
+mod terminal;
+mod toolkit;
+mod tui;
+mod tune;
+
+pub use animate::Animation;
+
+/// Represents a color in the Red-Green-Blue color space.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rgb {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+impl Rgb {
+    /// Returns the color as a CSS-style hex string (e.g., "#ffffff").
+    #[must_use]
+    pub fn hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// Returns `rrggbbaa` without a leading `#`, as swaylock and fuzzel expect.
+    #[must_use]
+    pub fn hex_rgba(self, alpha: u8) -> String {
+        format!("{:02x}{:02x}{:02x}{alpha:02x}", self.r, self.g, self.b)
+    }
+
+    /// Parses a `rrggbb` / `#rrggbb` string.
+    #[must_use]
+    pub fn from_hex(s: &str) -> Option<Self> {
+        let s = s.trim().trim_start_matches('#');
+        if s.len() != 6 {
+            return None;
+        }
+        let bytes = hex::decode(s).ok()?;
+        Some(Self {
+            r: bytes[0],
+            g: bytes[1],
+            b: bytes[2],
+        })
+    }
+
+    /// Calculates the relative luminance of the color.
+    /// Used to determine how "bright" a color appears to the human eye.
+    fn luminance(self) -> f32 {
+        0.2126f32.mul_add(f32::from(self.r), 0.7152 * f32::from(self.g))
+    }
+
+    /// Linearly blends towards `other`; `t = 0.0` returns `self`, `t = 1.0` returns `other`.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn mix(self, other: Self, t: f32) -> Self {
+        let lerp = |a: u8, b: u8| {
+            (f32::from(b) - f32::from(a))
+                .mul_add(t, f32::from(a))
+                .round() as u8
+        };
+        Self {
+            r: lerp(self.r, other.r),
+            g: lerp(self.g, other.g),
+            b: lerp(self.b, other.b),
+        }
+    }
+
+    /// A simple heuristic for color saturation by calculating the
+    /// distance between the most and least dominant channels.
+    fn saturation_proxy(self) -> u8 {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        max - min
+    }
+}
+
+impl Serialize for Rgb {
+    /// Colors are serialized as CSS-style hex strings so `theme.json` is easy to consume.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Rgb {
+    /// Colors in config files are written the same way, as `"#rrggbb"`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::from_hex(&s).ok_or_else(|| {
+            serde::de::Error::custom(format!("invalid color `{s}`: expected #rrggbb"))
+        })
+    }
+}
+
+/// The UI roles picked from a palette.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Roles {
+    pub bg: Rgb,
+    pub fg: Rgb,
+    pub accent: Rgb,
+    pub warn: Rgb,
+    pub ok: Rgb,
+}
+
+/// A fully resolved theme: the source image, its roles and a 16-color terminal palette.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Theme {
+    pub wallpaper: PathBuf,
+    pub roles: Roles,
+    pub colors: Vec<Rgb>,
+}
+
+/// Number of colors in a terminal palette (`color0` through `color15`).
+const PALETTE_SIZE: usize = 16;
+
+/// Assigns specific UI roles (background, foreground, accent, etc.)
+/// to colors based on their luminance and saturation.
+fn pick_roles(colors: &[Rgb]) -> Roles {
+    let fallback = Rgb {
+        r: 225,
+        g: 225,
+        b: 225,
+    };
+    let mut sorted = colors.to_vec();
+    // Sort by brightness (darkest to lightest)
+    sorted.sort_by(|a, b| a.luminance().total_cmp(&b.luminance()));
+
+    // Background is the darkest, Foreground is the lightest
+    let bg = sorted.first().copied().unwrap_or(fallback);
+    let fg = sorted.last().copied().unwrap_or(fallback);
+
+    // Accent is the most "vibrant" color in the pallete
+    let accent = sorted
+        .iter()
+        .copied()
+        .max_by_key(|c| c.saturation_proxy())
+        .unwrap_or(fg);
+
+    // Naive selection for status colors based on position in the sorted list
+    let warn = sorted.get(2).copied().unwrap_or(accent);
+    let ok = sorted.get(4).copied().unwrap_or(accent);
+
+    Roles {
+        bg,
+        fg,
+        accent,
+        warn,
+        ok,
+    }
+}
+
+/// Expands the extracted palette to exactly 16 colors.
+///
+/// `color_thief` may return fewer colors than requested for flat images,
+/// so the palette is repeated until every `colorN` slot is filled.
+fn sixteen(colors: &[Rgb], fallback: Rgb) -> Vec<Rgb> {
+    if colors.is_empty() {
+        return vec![fallback; PALETTE_SIZE];
+    }
+    colors.iter().copied().cycle().take(PALETTE_SIZE).collect()
+}
+
+/// Decodes an image and returns its dominant colors.
+#[cfg(feature = "extract")]
+fn extract_palette(image_path: &Path) -> anyhow::Result<Vec<Rgb>> {
+    // Load and downsample image for faster color extraction
+    let img = image::open(image_path)
+        .with_context(|| format!("Failed to open image for theming: {}", image_path.display()))?;
+    let img = img.resize(300, 300, image::imageops::FilterType::Nearest);
+    let buffer = img.to_rgb8();
+
+    // Extract dominant colors
+    let palette = color_thief::get_palette(buffer.as_raw(), color_thief::ColorFormat::Rgb, 10, 16)
+        .map_err(|e| anyhow::anyhow!("Color thief error: {e:?}"))?;
+
+    Ok(palette
+        .iter()
+        .map(|c| Rgb {
+            r: c.r,
+            g: c.g,
+            b: c.b,
+        })
+        .collect())
+}
+
+/// Without the `extract` feature only `scheme:` sources can build a theme.
+#[cfg(not(feature = "extract"))]
+fn extract_palette(_image_path: &Path) -> anyhow::Result<Vec<Rgb>> {
+    anyhow::bail!("built without the `extract` feature; use a `scheme:` theme source")
+}
+
+/// Builds a [`Theme`] from an extracted palette.
+fn theme_from_palette(wallpaper: &Path, colors: &[Rgb]) -> Theme {
+    let roles = pick_roles(colors);
+    Theme {
+        wallpaper: wallpaper.to_path_buf(),
+        roles,
+        colors: sixteen(colors, roles.fg),
+    }
+}
+
+/// Builds a [`Theme`] from an extracted palette after applying `[theme.tuning]`.
+fn tuned_theme(wallpaper: &Path, colors: &[Rgb], tuning: &TuningConfig) -> Theme {
+    let mut theme = theme_from_palette(wallpaper, &tune::palette(colors, tuning));
+    tune::accent(&mut theme.roles, tuning);
+    theme
+}
+
+/// Applies per-image overrides to a theme.
+///
+/// A pinned palette replaces the extracted colors and the roles are re-picked
+/// from it; pinned roles are applied last.
+#[must_use]
+pub fn with_overrides(theme: Theme, image_override: &ImageOverride) -> Theme {
+    let mut theme = match image_override.palette.as_deref() {
+        Some(palette) if !palette.is_empty() => theme_from_palette(&theme.wallpaper, palette),
+        _ => theme,
+    };
+    image_override.roles.apply(&mut theme.roles);
+    theme
+}
+
+/// Decodes an image and builds its [`Theme`] without writing or reloading anything.
+///
+/// # Errors
+///
+/// Returns [`Error::Theme`] if the image cannot be opened or the palette cannot be extracted.
+pub fn extract_theme(image_path: &Path, tuning: &TuningConfig) -> Result<Theme> {
+    let colors = extract_palette(image_path).map_err(Error::Theme)?;
+    Ok(tuned_theme(image_path, &colors, tuning))
+}
+
+/// Builds one [`Theme`] from several images by interleaving their palettes.
+///
+/// Each palette is ordered by dominance, so taking colors round-robin keeps
+/// every image's strongest colors in the first 16 slots. `wallpaper` in the
+/// result is the first image.
+///
+/// # Errors
+///
+/// Returns [`Error::Theme`] if `image_paths` is empty or any image cannot be themed.
+pub fn extract_blended_theme(image_paths: &[PathBuf], tuning: &TuningConfig) -> Result<Theme> {
+    let first = image_paths
+        .first()
+        .ok_or_else(|| Error::Theme(anyhow::anyhow!("no wallpaper is assigned to any monitor")))?;
+    let palettes = image_paths
+        .iter()
+        .map(|p| extract_palette(p))
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map_err(Error::Theme)?;
+
+    let longest = palettes.iter().map(Vec::len).max().unwrap_or(0);
+    let blended: Vec<Rgb> = (0..longest)
+        .flat_map(|i| palettes.iter().filter_map(move |p| p.get(i).copied()))
+        .collect();
+
+    Ok(tuned_theme(first, &blended, tuning))
+}
+
+/// The user's config directory (`~/.config`).
+pub(crate) fn config_dir() -> anyhow::Result<PathBuf> {
+    dirs::config_dir().ok_or_else(|| anyhow::anyhow!("Could not find config directory"))
+}
+
+/// The directory generated theme files live in: `theme.dir`, or
+/// `~/.config/randpaper/themes` when unset.
+fn theme_dir(config: &ThemeConfig) -> anyhow::Result<PathBuf> {
+    match &config.dir {
+        Some(dir) => Ok(expand_tilde(dir)),
+        None => Ok(config_dir()?.join("randpaper/themes")),
+    }
+}
+
+/// Performs an atomic write by writing to a temporary file and then renaming it.
+/// This prevents partial writes if the power cuts or the process crashes.
+fn atomic_write(path: &Path, contents: impl AsRef<[u8]>) -> anyhow::Result<()> {
+    let dir = path.parent().context("path has no parent")?;
+    fs::create_dir_all(dir)?;
+
+    let file_name = path
+        .file_name()
+        .context("path has no filename")?
+        .to_string_lossy();
+
+    // Use .display() if you ever print the path,
+    // but for building the filename, string lossy is perfect.
+    let tmp_name = format!(".{}.tmp.{}", file_name, std::process::id());
+    let tmp_path = dir.join(tmp_name);
+
+    fs::write(&tmp_path, contents)
+        .with_context(|| format!("failed to write temp file: {}", tmp_path.display()))?;
+
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("failed to commit atomic write to {}", path.display()))?;
+
+    Ok(())
+}
+
+/// Generates a CSS file for Waybar containing @define-color variables
+/// based on the theme roles.
+fn write_waybar_css(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles {
+        bg,
+        fg,
+        accent,
+        warn,
+        ok,
+    } = theme.roles;
+
+    let mut css = String::new();
+    let _ = writeln!(css, "/* auto-generated by randpaper */");
+    let _ = writeln!(css, "@define-color rp_bg {};", bg.hex());
+    let _ = writeln!(css, "@define-color rp_fg {};", fg.hex());
+    let _ = writeln!(css, "@define-color rp_accent {};", accent.hex());
+    let _ = writeln!(css, "@define-color rp_warn {};", warn.hex());
+    let _ = writeln!(css, "@define-color rp_ok {};", ok.hex());
+    let _ = writeln!(css, "@define-color rp_border {};", accent.hex());
+    let _ = writeln!(css, "@define-color rp_muted {};", bg.hex());
+
+    atomic_write(path, &css)
+}
+
+/// Writes the resolved theme as `theme.json` so scripts can read the current roles and palette.
+fn write_theme_json(theme_dir: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let json = serde_json::to_string_pretty(theme).context("serialize theme.json")?;
+    atomic_write(&theme_dir.join("theme.json"), &json)
+}
+
+/// Ensures the Waybar theme file exists with a default Catppuccin-style palette.
+/// Call this once at startup to prevent Waybar from crashing on @import.
+///
+/// # Errors
+///
+/// Returns [`Error::Theme`] if the theme directory or file cannot be written.
+pub fn ensure_theme_exists(config: &ThemeConfig) -> Result<()> {
+    write_default_theme(config).map_err(Error::Theme)
+}
+
+fn write_default_theme(config: &ThemeConfig) -> anyhow::Result<()> {
+    let theme_dir = theme_dir(config)?;
+
+    let waybar_css = theme_dir.join("waybar.css");
+
+    // If the file already exists, we're done
+    if waybar_css.exists() {
+        return Ok(());
+    }
+
+    // Create directory and write a default theme
+    fs::create_dir_all(&theme_dir)?;
+
+    let default_css = r"/* Default randpaper theme - will be replaced on first wallpaper change */
+@define-color rp_bg #1e1e2e;
+@define-color rp_fg #cdd6f4;
+@define-color rp_accent #89b4fa;
+@define-color rp_warn #f9e2af;
+@define-color rp_ok #a6e3a1;
+@define-color rp_border #89b4fa;
+@define-color rp_muted #313244;
+";
+
+    atomic_write(&waybar_css, default_css)?;
+    log::info!("Created default theme file at {}", waybar_css.display());
+
+    Ok(())
+}
+
+/// Writes one theme output to a file (or directory, for multi-file targets).
+type Writer<'d> = Box<dyn Fn(&Path, &Theme) -> anyhow::Result<()> + 'd>;
+
+/// Expands a leading `~/` in a user-supplied path.
+#[must_use]
+pub fn expand_tilde(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// A theme being extracted on the blocking thread pool.
+///
+/// The daemon starts one for the *next* rotation right after the current one, so
+/// the image decode and palette extraction happen during the idle period and the
+/// following rotation only has to write files and signal reloads.
+pub struct PendingTheme {
+    task: JoinHandle<Result<Theme>>,
+}
+
+impl PendingTheme {
+    /// Starts building the theme for `image_paths` in the background.
+    ///
+    /// If the first image maps to a `scheme:` source the scheme file is loaded;
+    /// otherwise a single image is extracted directly and several are blended.
+    #[must_use]
+    pub fn spawn(image_paths: Vec<PathBuf>, config: &ThemeConfig) -> Self {
+        let source = image_paths
+            .first()
+            .map_or(&config.source, |img| scheme::source_for(config, img))
+            .clone();
+        let tuning = config.tuning.clone();
+
+        let task = tokio::task::spawn_blocking(move || match (source, image_paths.as_slice()) {
+            (ThemeSource::Scheme(scheme), [first, ..]) => {
+                scheme::load_scheme(&expand_tilde(&scheme), first).map_err(Error::Theme)
+            }
+            (_, [single]) => extract_theme(single, &tuning),
+            (_, many) => extract_blended_theme(many, &tuning),
+        });
+        Self { task }
+    }
+}
+
+/// Waits for a [`PendingTheme`] and applies it on the blocking thread pool, so
+/// image decoding, file writes and reload commands never stall the async runtime.
+///
+/// With an [`Animation`] the targets fade from the previous theme over several
+/// frames; `theme.timeout` must leave room for it.
+///
+/// If `theme.timeout` elapses first the error is returned right away; the
+/// blocking work itself cannot be cancelled and finishes in the background.
+///
+/// # Errors
+///
+/// Returns [`Error::Config`] if `theme.timeout` is not a valid duration, and
+/// [`Error::Theme`] if extraction or theming fails, panics, or does not finish in time.
+pub async fn apply_pending(
+    pending: PendingTheme,
+    config: ThemeConfig,
+    animation: Option<Animation>,
+) -> Result<Theme> {
+    let limit = parse_duration::parse(&config.timeout)
+        .map_err(|e| Error::Config(anyhow::anyhow!("invalid theme.timeout: {e}")))?;
+
+    let work = async move {
+        let theme = pending
+            .task
+            .await
+            .context("theme extraction task panicked")??;
+        tokio::task::spawn_blocking(move || apply_theme(theme, &config, animation))
+            .await
+            .context("theme update task panicked")?
+    };
+
+    tokio::time::timeout(limit, work)
+        .await
+        .with_context(|| format!("theme update timed out after {limit:?}"))
+        .and_then(|theme| theme)
+        .map_err(Error::Theme)
+}
+
+/// Extracts and applies the theme for `image_paths` in one go (used by one-shot mode).
+///
+/// # Errors
+///
+/// See [`apply_pending`].
+pub async fn update_theme(
+    image_paths: Vec<PathBuf>,
+    config: ThemeConfig,
+    animation: Option<Animation>,
+) -> Result<Theme> {
+    apply_pending(PendingTheme::spawn(image_paths, &config), config, animation).await
+}
+
+/// Targets redrawn on every animation frame. Their reloads are a socket message
+/// (kitty) or a signal (foot); every other target is written and reloaded once,
+/// with the final theme, so a fade never spawns `xrdb`, `nvim` or `tmux` per frame.
+const ANIMATED_TARGETS: &[&str] = &["kitty", "foot"];
+
+/// Applies an already extracted theme.
+/// 0. Applies the wallpaper's sidecar / `overrides.toml` entry, if any.
+/// 1. Writes `theme.json` plus every enabled `[theme.targets.*]` output
+///    (Waybar, Ghostty, Kitty and Foot by default).
+/// 2. Runs each written target's reload action.
+///
+/// With an [`Animation`], the [`ANIMATED_TARGETS`] are first stepped through
+/// every intermediate frame between the previous `theme.json` and the new theme.
+///
+/// Returns the [`Theme`] so callers can push it to the compositor as well.
+fn apply_theme(
+    theme: Theme,
+    config: &ThemeConfig,
+    animation: Option<Animation>,
+) -> anyhow::Result<Theme> {
+    log::info!("updating theme for image: {}", theme.wallpaper.display());
+    let image_override = overrides::for_image(&theme.wallpaper);
+    let theme = with_overrides(theme, &image_override);
+
+    let theme_dir = theme_dir(config)?;
+
+    // Read the outgoing theme before theme.json is replaced
+    let frames = match animation {
+        Some(anim) => match animate::previous_theme(&theme_dir.join("theme.json")) {
+            Ok(prev) if prev.roles != theme.roles || prev.colors != theme.colors => {
+                anim.frames(&prev, &theme)
+            }
+            Ok(_) => vec![theme.clone()],
+            Err(e) => {
+                log::debug!("not animating theme: {e:#}");
+                vec![theme.clone()]
+            }
+        },
+        None => vec![theme.clone()],
+    };
+    let interval = animation.map_or(Duration::ZERO, Animation::interval);
+    let (last, fade) = frames.split_last().expect("frames end with the new theme");
+
+    // 1. theme.json is always written so scripts have a stable place to read from
+    write_theme_json(&theme_dir, &theme)?;
+
+    // 2. Fade the cheap-to-reload targets through the intermediate frames
+    for frame in fade {
+        let written = write_targets(frame, config, &theme_dir, |name| {
+            ANIMATED_TARGETS.contains(&name)
+        })?;
+        for (name, target, path) in written {
+            reload::reload(name, &target.reload, &path);
+        }
+        thread::sleep(interval);
+    }
+
+    // 3. Write every enabled target (Atomic) with the final theme
+    let written = write_targets(last, config, &theme_dir, |_| true)?;
+    // Small delay to ensure filesystem has flushed the writes
+    thread::sleep(Duration::from_millis(100));
+
+    // 4. Reload whatever consumes the files we just wrote
+    for (name, target, path) in written {
+        reload::reload(name, &target.reload, &path);
+    }
+
+    log::info!(
+        "Updated themes in {} ({} frame(s))",
+        theme_dir.display(),
+        frames.len()
+    );
+    Ok(theme)
+}
+
+/// Every built-in target with its default path and writer.
+#[allow(clippy::too_many_lines)]
+fn target_table<'a, 'd>(
+    config: &'a ThemeConfig,
+    theme_dir: &'d Path,
+) -> anyhow::Result<[(&'static str, &'a TargetConfig, PathBuf, Writer<'d>); 22]> {
+    let t = &config.targets;
+    Ok([
+        (
+            "waybar",
+            &t.waybar,
+            theme_dir.join("waybar.css"),
+            Box::new(write_waybar_css),
+        ),
+        (
+            "ghostty",
+            &t.ghostty,
+            theme_dir.join("ghostty.config"),
+            Box::new(terminal::write_ghostty),
+        ),
+        (
+            "kitty",
+            &t.kitty,
+            theme_dir.join("kitty.conf"),
+            Box::new(terminal::write_kitty),
+        ),
+        (
+            "foot",
+            &t.foot,
+            theme_dir.join("foot.ini"),
+            Box::new(terminal::write_foot),
+        ),
+        (
+            "alacritty",
+            &t.alacritty,
+            theme_dir.join("alacritty.toml"),
+            Box::new(terminal::write_alacritty),
+        ),
+        (
+            "wezterm",
+            &t.wezterm,
+            // wezterm only loads schemes from its own `colors/` directory
+            config_dir()?.join("wezterm/colors/randpaper.toml"),
+            Box::new(terminal::write_wezterm),
+        ),
+        (
+            "xresources",
+            &t.xresources,
+            theme_dir.join("Xresources"),
+            Box::new(terminal::write_xresources),
+        ),
+        (
+            "pywal",
+            &t.pywal,
+            pywal::wal_dir()?,
+            Box::new(pywal::write_pywal),
+        ),
+        (
+            "gtk",
+            &t.gtk,
+            theme_dir.join("gtk.css"),
+            Box::new(toolkit::write_gtk_css),
+        ),
+        (
+            "qt",
+            &t.qt,
+            theme_dir.to_path_buf(),
+            Box::new(toolkit::write_qt_schemes),
+        ),
+        (
+            "swaylock",
+            &t.swaylock,
+            theme_dir.join("swaylock.conf"),
+            Box::new(|path, theme| lock::write_swaylock(path, theme, theme_dir)),
+        ),
+        (
+            "hyprlock",
+            &t.hyprlock,
+            theme_dir.join("hyprlock.conf"),
+            Box::new(|path, theme| lock::write_hyprlock(path, theme, theme_dir)),
+        ),
+        (
+            "mako",
+            &t.mako,
+            theme_dir.join("mako.conf"),
+            Box::new(popup::write_mako),
+        ),
+        (
+            "dunst",
+            &t.dunst,
+            theme_dir.join("dunstrc"),
+            Box::new(popup::write_dunst),
+        ),
+        (
+            "fuzzel",
+            &t.fuzzel,
+            theme_dir.join("fuzzel.ini"),
+            Box::new(popup::write_fuzzel),
+        ),
+        (
+            "rofi",
+            &t.rofi,
+            theme_dir.join("rofi.rasi"),
+            Box::new(popup::write_rofi),
+        ),
+        (
+            "wofi",
+            &t.wofi,
+            theme_dir.join("wofi.css"),
+            Box::new(popup::write_wofi),
+        ),
+        (
+            "neovim",
+            &t.neovim,
+            theme_dir.join("nvim/colors/randpaper.lua"),
+            Box::new(tui::write_neovim),
+        ),
+        (
+            "tmux",
+            &t.tmux,
+            theme_dir.join("tmux.conf"),
+            Box::new(tui::write_tmux),
+        ),
+        (
+            "btop",
+            &t.btop,
+            theme_dir.join("btop.theme"),
+            Box::new(tui::write_btop),
+        ),
+        (
+            "fzf",
+            &t.fzf,
+            theme_dir.join("fzf.sh"),
+            Box::new(tui::write_fzf),
+        ),
+        (
+            "starship",
+            &t.starship,
+            config_dir()?.join("starship.toml"),
+            Box::new(tui::write_starship),
+        ),
+    ])
+}
+
+/// Writes every enabled target accepted by `include` atomically and returns each
+/// written target with its path.
+///
+/// A target that fails to write is logged and skipped, so one broken target
+/// never keeps the others from updating.
+fn write_targets<'a>(
+    theme: &Theme,
+    config: &'a ThemeConfig,
+    theme_dir: &Path,
+    include: impl Fn(&str) -> bool,
+) -> anyhow::Result<Vec<(&'static str, &'a TargetConfig, PathBuf)>> {
+    let mut written = Vec::new();
+    for (name, target, default_path, write) in target_table(config, theme_dir)? {
+        if !target.enabled || !include(name) {
+            continue;
+        }
+        let path = target.path.as_deref().map_or(default_path, expand_tilde);
+        match write(&path, theme) {
+            Ok(()) => written.push((name, target, path)),
+            Err(e) => log::warn!(
+                target_name = name,
+                error:% = format!("{e:#}");
+                "theme target write failed"
+            ),
+        }
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Targets;
+    use crate::test_util::StubDir;
+
+    #[test]
+    fn a_failing_target_does_not_stop_the_others() {
+        let dir = StubDir::new();
+        let mut config = ThemeConfig {
+            targets: Targets::none(),
+            ..ThemeConfig::default()
+        };
+        config.targets.waybar.enabled = true;
+        config.targets.kitty.enabled = true;
+        // The lock image cannot be made from a wallpaper that does not exist
+        config.targets.swaylock.enabled = true;
+
+        let color = Rgb::from_hex("#336699").expect("valid hex");
+        let theme = theme_from_palette(&dir.path("missing.png"), &[color]);
+        let written = write_targets(&theme, &config, &dir.path(""), |_| true).expect("write");
+
+        let names: Vec<&str> = written.iter().map(|(name, ..)| *name).collect();
+        assert_eq!(names, ["waybar", "kitty"]);
+        assert!(dir.path("waybar.css").is_file());
+        assert!(dir.path("kitty.conf").is_file());
+        assert!(!dir.path("swaylock.conf").exists());
+    }
+}
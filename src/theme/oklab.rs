@@ -0,0 +1,169 @@
+use super::Rgb;
+
+/// A color in the `OKLab` perceptual space (see <https://bottosson.github.io/posts/oklab/>).
+///
+/// Blending here keeps lightness and hue changes even, where a plain RGB blend
+/// between two saturated colors passes through muddy grays.
+#[derive(Clone, Copy, Debug)]
+pub struct Oklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+/// Linear sRGB to LMS cone response.
+const RGB_TO_LMS: [[f32; 3]; 3] = [
+    [0.412_221_46, 0.536_332_55, 0.051_445_995],
+    [0.211_903_5, 0.680_699_5, 0.107_396_96],
+    [0.088_302_46, 0.281_718_85, 0.629_978_7],
+];
+
+/// Non-linear LMS to Lab.
+const LMS_TO_LAB: [[f32; 3]; 3] = [
+    [0.210_454_26, 0.793_617_8, -0.004_072_047],
+    [1.977_998_5, -2.428_592_2, 0.450_593_7],
+    [0.025_904_037, 0.782_771_77, -0.808_675_77],
+];
+
+/// Lab to non-linear LMS.
+const LAB_TO_LMS: [[f32; 3]; 3] = [
+    [1.0, 0.396_337_78, 0.215_803_76],
+    [1.0, -0.105_561_346, -0.063_854_17],
+    [1.0, -0.089_484_18, -1.291_485_5],
+];
+
+/// LMS cone response to linear sRGB.
+const LMS_TO_RGB: [[f32; 3]; 3] = [
+    [4.076_741_7, -3.307_711_6, 0.230_969_94],
+    [-1.268_438, 2.609_757_4, -0.341_319_38],
+    [-0.004_196_086_3, -0.703_418_6, 1.707_614_7],
+];
+
+fn mul(m: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    m.map(|row| row[0].mul_add(v[0], row[1].mul_add(v[1], row[2] * v[2])))
+}
+
+fn to_linear(c: u8) -> f32 {
+    let c = f32::from(c) / 255.0;
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn from_linear(c: f32) -> u8 {
+    let c = if c <= 0.003_130_8 {
+        12.92 * c
+    } else {
+        1.055f32.mul_add(c.powf(1.0 / 2.4), -0.055)
+    };
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl From<Rgb> for Oklab {
+    fn from(c: Rgb) -> Self {
+        let lms = mul(
+            &RGB_TO_LMS,
+            [to_linear(c.r), to_linear(c.g), to_linear(c.b)],
+        );
+        let [l, a, b] = mul(&LMS_TO_LAB, lms.map(f32::cbrt));
+        Self { l, a, b }
+    }
+}
+
+impl From<Oklab> for Rgb {
+    /// Out-of-gamut colors are clamped per channel.
+    fn from(c: Oklab) -> Self {
+        let lms = mul(&LAB_TO_LMS, [c.l, c.a, c.b]).map(|x| x * x * x);
+        let [r, g, b] = mul(&LMS_TO_RGB, lms).map(from_linear);
+        Self { r, g, b }
+    }
+}
+
+impl Oklab {
+    /// Linearly interpolates towards `other`; `t = 0.0` returns `self`.
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let lerp = |a: f32, b: f32| (b - a).mul_add(t, a);
+        Self {
+            l: lerp(self.l, other.l),
+            a: lerp(self.a, other.a),
+            b: lerp(self.b, other.b),
+        }
+    }
+
+    /// Builds a color from `OKLCh` lightness, chroma and hue (radians).
+    pub fn from_lch(l: f32, chroma: f32, hue: f32) -> Self {
+        Self {
+            l,
+            a: chroma * hue.cos(),
+            b: chroma * hue.sin(),
+        }
+    }
+
+    /// Distance from the neutral axis; roughly 0.0-0.37 for sRGB colors.
+    pub fn chroma(self) -> f32 {
+        self.a.hypot(self.b)
+    }
+
+    /// Hue angle in radians.
+    pub fn hue(self) -> f32 {
+        self.b.atan2(self.a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgb(r: u8, g: u8, b: u8) -> Rgb {
+        Rgb { r, g, b }
+    }
+
+    #[test]
+    fn round_trips_srgb_colors() {
+        for value in (0..=255).step_by(15) {
+            for color in [
+                rgb(value, 0, 0),
+                rgb(0, value, 0),
+                rgb(0, 0, value),
+                rgb(value, value, value),
+                rgb(value, 255 - value, value / 2),
+            ] {
+                assert_eq!(Rgb::from(Oklab::from(color)), color);
+            }
+        }
+    }
+
+    #[test]
+    fn grays_sit_on_the_neutral_axis() {
+        let white = Oklab::from(rgb(255, 255, 255));
+        let black = Oklab::from(rgb(0, 0, 0));
+        assert!((white.l - 1.0).abs() < 1e-3, "{white:?}");
+        assert!(black.l.abs() < 1e-3, "{black:?}");
+        assert!(Oklab::from(rgb(128, 128, 128)).chroma() < 1e-3);
+        assert!(Oklab::from(rgb(255, 0, 0)).chroma() > 0.2);
+    }
+
+    #[test]
+    fn lch_round_trips_and_lerp_hits_its_ends() {
+        let teal = Oklab::from(rgb(40, 160, 150));
+        let back = Oklab::from_lch(teal.l, teal.chroma(), teal.hue());
+        assert_eq!(Rgb::from(back), rgb(40, 160, 150));
+
+        let red = Oklab::from(rgb(200, 40, 40));
+        assert_eq!(Rgb::from(teal.lerp(red, 0.0)), rgb(40, 160, 150));
+        assert_eq!(Rgb::from(teal.lerp(red, 1.0)), rgb(200, 40, 40));
+        let mid = teal.lerp(red, 0.5);
+        assert!((mid.l - f32::midpoint(teal.l, red.l)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn clamps_out_of_gamut_colors() {
+        let vivid = Oklab::from_lch(0.7, 0.5, 0.0);
+        let Rgb { r, g, b } = vivid.into();
+        assert_eq!(r, 255);
+        assert!(g < r && b < r);
+    }
+}
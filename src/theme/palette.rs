@@ -0,0 +1,200 @@
+use super::{Rgb, Theme, extract_theme, with_overrides};
+use crate::cli::{PaletteFormat, TuningConfig};
+use crate::error::{Error, Result};
+#[cfg(feature = "extract")]
+use anyhow::Context;
+use std::fmt::Write;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Side length (in pixels) of a single swatch in the PNG strip.
+#[cfg(feature = "extract")]
+const SWATCH_PX: u32 = 64;
+
+/// Renders a theme as truecolor terminal swatches, one line per color.
+fn terminal_swatches(theme: &Theme) -> String {
+    fn line(out: &mut String, label: &str, c: Rgb) {
+        let _ = writeln!(
+            out,
+            "\x1b[48;2;{};{};{}m      \x1b[0m {} {label}",
+            c.r,
+            c.g,
+            c.b,
+            c.hex()
+        );
+    }
+
+    let mut out = String::new();
+    let roles = &theme.roles;
+    for (label, c) in [
+        ("bg", roles.bg),
+        ("fg", roles.fg),
+        ("accent", roles.accent),
+        ("warn", roles.warn),
+        ("ok", roles.ok),
+    ] {
+        line(&mut out, label, c);
+    }
+    out.push('\n');
+    for (i, c) in theme.colors.iter().enumerate() {
+        line(&mut out, &format!("color{i}"), *c);
+    }
+    out
+}
+
+/// Renders a theme as a PNG swatch strip.
+///
+/// The top row holds the 16 palette colors; the bottom row splits the same
+/// width evenly between the five roles.
+#[cfg(feature = "extract")]
+fn write_png(theme: &Theme, path: &Path) -> anyhow::Result<()> {
+    let roles = [
+        theme.roles.bg,
+        theme.roles.fg,
+        theme.roles.accent,
+        theme.roles.warn,
+        theme.roles.ok,
+    ];
+    let cols = u32::try_from(theme.colors.len()).context("palette too large")?;
+    let width = SWATCH_PX * cols.max(1);
+    let height = SWATCH_PX * 2;
+
+    let img = image::RgbImage::from_fn(width, height, |x, y| {
+        let c = if y < SWATCH_PX {
+            theme.colors[(x / SWATCH_PX) as usize]
+        } else {
+            roles[(x * 5 / width) as usize]
+        };
+        image::Rgb([c.r, c.g, c.b])
+    });
+
+    img.save(path)
+        .with_context(|| format!("failed to write swatch PNG: {}", path.display()))
+}
+
+/// PNG output needs the `image` crate, which only the `extract` feature pulls in.
+#[cfg(not(feature = "extract"))]
+fn write_png(_theme: &Theme, _path: &Path) -> anyhow::Result<()> {
+    anyhow::bail!("built without the `extract` feature; use --format json or term")
+}
+
+/// Runs the `palette` subcommand: extracts the theme for `image` and prints
+/// it in the requested format without writing any theme files or reloading apps.
+///
+/// `[theme.tuning]` and per-image overrides are applied, so the preview matches
+/// what a rotation would write.
+///
+/// # Errors
+///
+/// Returns [`Error::Theme`] if the image cannot be themed or the output cannot be written.
+pub fn run(
+    image: &Path,
+    format: PaletteFormat,
+    output: Option<PathBuf>,
+    tuning: &TuningConfig,
+) -> Result<()> {
+    let theme = with_overrides(
+        extract_theme(image, tuning)?,
+        &crate::overrides::for_image(image),
+    );
+    print(&theme, format, output, &mut io::stdout().lock()).map_err(Error::Theme)
+}
+
+/// Prints `theme` to `stdout`, or writes the PNG strip and prints its path.
+fn print(
+    theme: &Theme,
+    format: PaletteFormat,
+    output: Option<PathBuf>,
+    stdout: &mut impl io::Write,
+) -> anyhow::Result<()> {
+    match format {
+        PaletteFormat::Json => {
+            writeln!(stdout, "{}", serde_json::to_string_pretty(theme)?)?;
+        }
+        PaletteFormat::Term => {
+            write!(stdout, "{}", terminal_swatches(theme))?;
+        }
+        PaletteFormat::Png => {
+            let path = output.unwrap_or_else(|| PathBuf::from("palette.png"));
+            write_png(theme, &path)?;
+            writeln!(stdout, "{}", path.display())?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::theme_from_palette;
+
+    fn theme() -> Theme {
+        let colors: Vec<Rgb> = ["#1d2021", "#cc241d", "#98971a", "#d79921"]
+            .iter()
+            .map(|hex| Rgb::from_hex(hex).expect("valid hex"))
+            .collect();
+        theme_from_palette(Path::new("wall.png"), &colors)
+    }
+
+    fn printed(theme: &Theme, format: PaletteFormat, output: Option<PathBuf>) -> String {
+        let mut out = Vec::new();
+        print(theme, format, output, &mut out).expect("print");
+        String::from_utf8(out).expect("utf-8")
+    }
+
+    #[test]
+    fn term_prints_one_swatch_per_role_and_color() {
+        let theme = theme();
+        let out = printed(&theme, PaletteFormat::Term, None);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 5 + 1 + theme.colors.len());
+
+        let bg = theme.roles.bg;
+        assert_eq!(
+            lines[0],
+            format!(
+                "\x1b[48;2;{};{};{}m      \x1b[0m {} bg",
+                bg.r,
+                bg.g,
+                bg.b,
+                bg.hex()
+            )
+        );
+        assert!(lines[4].ends_with(" ok"), "{}", lines[4]);
+        assert!(lines[5].is_empty());
+        assert!(lines[6].ends_with(&format!("{} color0", theme.colors[0].hex())));
+        assert!(lines.last().expect("lines").ends_with(" color15"));
+    }
+
+    #[test]
+    fn json_round_trips_the_theme() {
+        let theme = theme();
+        let out = printed(&theme, PaletteFormat::Json, None);
+        let parsed: Theme = serde_json::from_str(&out).expect("valid json");
+        assert_eq!(parsed.wallpaper, theme.wallpaper);
+        assert_eq!(parsed.roles, theme.roles);
+        assert_eq!(parsed.colors, theme.colors);
+    }
+
+    #[cfg(feature = "extract")]
+    #[test]
+    fn png_draws_the_palette_over_the_roles() {
+        let dir = crate::test_util::StubDir::new();
+        let path = dir.path("strip.png");
+        let theme = theme();
+        let out = printed(&theme, PaletteFormat::Png, Some(path.clone()));
+        assert_eq!(out.trim_end(), path.display().to_string());
+
+        let img = image::open(&path).expect("png").to_rgb8();
+        let cols = u32::try_from(theme.colors.len()).expect("cols");
+        assert_eq!(img.dimensions(), (SWATCH_PX * cols, SWATCH_PX * 2));
+        let px = |x, y| {
+            let [r, g, b] = img.get_pixel(x, y).0;
+            Rgb { r, g, b }
+        };
+        assert_eq!(px(SWATCH_PX, 0), theme.colors[1]);
+        assert_eq!(px(0, SWATCH_PX), theme.roles.bg);
+        assert_eq!(px(img.width() - 1, SWATCH_PX), theme.roles.ok);
+    }
+}
@@ -0,0 +1,131 @@
+use super::{Roles, Theme, atomic_write};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Writes a mako include (`include=<path>`); urgency is shown through the border color.
+pub fn write_mako(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles {
+        bg,
+        fg,
+        accent,
+        warn,
+        ok,
+    } = theme.roles;
+
+    let mut conf = String::from("# Auto-generated by randpaper\n");
+    let _ = writeln!(conf, "background-color={}", bg.hex());
+    let _ = writeln!(conf, "text-color={}", fg.hex());
+    let _ = writeln!(conf, "border-color={}", accent.hex());
+    let _ = writeln!(conf, "progress-color=over {}", accent.hex());
+    let _ = writeln!(conf, "\n[urgency=low]\nborder-color={}", ok.hex());
+    let _ = writeln!(conf, "\n[urgency=critical]\nborder-color={}", warn.hex());
+    atomic_write(path, &conf)
+}
+
+/// Writes dunst urgency sections, suitable for a `dunstrc.d/` drop-in.
+pub fn write_dunst(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles {
+        bg,
+        fg,
+        accent,
+        warn,
+        ok,
+    } = theme.roles;
+
+    let mut conf = String::from("# Auto-generated by randpaper\n");
+    let _ = writeln!(conf, "[global]\n    highlight = \"{}\"", accent.hex());
+    for (section, frame) in [
+        ("urgency_low", ok),
+        ("urgency_normal", accent),
+        ("urgency_critical", warn),
+    ] {
+        let _ = writeln!(conf, "\n[{section}]");
+        let _ = writeln!(conf, "    background = \"{}\"", bg.hex());
+        let _ = writeln!(conf, "    foreground = \"{}\"", fg.hex());
+        let _ = writeln!(conf, "    frame_color = \"{}\"", frame.hex());
+    }
+    atomic_write(path, &conf)
+}
+
+/// Writes a fuzzel `[colors]` section (`include=<path>` in `fuzzel.ini`).
+pub fn write_fuzzel(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles { bg, fg, accent, .. } = theme.roles;
+
+    let mut conf = String::from("# Auto-generated by randpaper\n[colors]\n");
+    for (key, value) in [
+        ("background", bg.hex_rgba(0xf2)),
+        ("text", fg.hex_rgba(0xff)),
+        ("prompt", fg.hex_rgba(0xff)),
+        ("input", fg.hex_rgba(0xff)),
+        ("match", accent.hex_rgba(0xff)),
+        ("selection", accent.hex_rgba(0xff)),
+        ("selection-text", bg.hex_rgba(0xff)),
+        ("selection-match", bg.hex_rgba(0xff)),
+        ("border", accent.hex_rgba(0xff)),
+    ] {
+        let _ = writeln!(conf, "{key}={value}");
+    }
+    atomic_write(path, &conf)
+}
+
+/// Writes rofi variables (`@import "<path>"`), used as `@rp-bg`, `@rp-accent`, ...
+pub fn write_rofi(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles {
+        bg,
+        fg,
+        accent,
+        warn,
+        ok,
+    } = theme.roles;
+
+    let mut rasi = String::from("/* Auto-generated by randpaper */\n* {\n");
+    for (name, color) in [
+        ("bg", bg),
+        ("fg", fg),
+        ("accent", accent),
+        ("warn", warn),
+        ("ok", ok),
+    ] {
+        let _ = writeln!(rasi, "    rp-{name}: {};", color.hex());
+    }
+    rasi.push_str("}\n");
+    atomic_write(path, &rasi)
+}
+
+/// Writes a complete wofi stylesheet (`wofi --style <path>`).
+pub fn write_wofi(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles { bg, fg, accent, .. } = theme.roles;
+
+    let mut css = String::from("/* Auto-generated by randpaper */\n");
+    let _ = writeln!(css, "@define-color rp_bg {};", bg.hex());
+    let _ = writeln!(css, "@define-color rp_fg {};", fg.hex());
+    let _ = writeln!(css, "@define-color rp_accent {};", accent.hex());
+    let _ = writeln!(css, "@define-color rp_surface {};", bg.mix(fg, 0.08).hex());
+    css.push_str(
+        r"
+window {
+    background-color: @rp_bg;
+    border: 2px solid @rp_accent;
+}
+
+#input {
+    background-color: @rp_surface;
+    color: @rp_fg;
+    border: none;
+}
+
+#text {
+    color: @rp_fg;
+}
+
+#entry:selected {
+    background-color: @rp_accent;
+}
+
+#entry:selected #text {
+    color: @rp_bg;
+}
+",
+    );
+    atomic_write(path, &css)
+}
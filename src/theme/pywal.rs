@@ -0,0 +1,127 @@
+use super::{Roles, Theme, atomic_write};
+use anyhow::Context;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Returns the directory pywal consumers read from (`~/.cache/wal`).
+pub fn wal_dir() -> anyhow::Result<PathBuf> {
+    Ok(dirs::cache_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not find cache directory"))?
+        .join("wal"))
+}
+
+/// Writes the pywal output set (`colors.json`, `colors.sh`, `colors.Xresources`,
+/// `colors.css`, `colors` and `wal`) so tools that read `~/.cache/wal` pick up
+/// the randpaper palette without any changes.
+///
+/// The cursor color follows the foreground, matching what `wal` itself emits.
+pub fn write_pywal(dir: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles { bg, fg, .. } = theme.roles;
+    let colors = &theme.colors;
+    let wallpaper = theme.wallpaper.to_string_lossy();
+
+    // colors.json
+    let json = serde_json::json!({
+        "wallpaper": wallpaper,
+        "alpha": "100",
+        "special": {
+            "background": bg.hex(),
+            "foreground": fg.hex(),
+            "cursor": fg.hex(),
+        },
+        "colors": colors
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (format!("color{i}"), serde_json::Value::String(c.hex())))
+            .collect::<serde_json::Map<_, _>>(),
+    });
+    let json = serde_json::to_string_pretty(&json).context("pywal: serialize colors.json")?;
+    atomic_write(&dir.join("colors.json"), &json)?;
+
+    // colors.sh is sourced by shells, so a `'` in the file name must not end the quoting
+    let mut sh = String::from("# Shell variables\n# Generated by randpaper\n");
+    let _ = writeln!(sh, "wallpaper='{}'\n", wallpaper.replace('\'', r"'\''"));
+    let _ = writeln!(sh, "# Special");
+    let _ = writeln!(sh, "background='{}'", bg.hex());
+    let _ = writeln!(sh, "foreground='{}'", fg.hex());
+    let _ = writeln!(sh, "cursor='{}'\n", fg.hex());
+    let _ = writeln!(sh, "# Colors");
+    for (i, c) in colors.iter().enumerate() {
+        let _ = writeln!(sh, "color{i}='{}'", c.hex());
+    }
+    atomic_write(&dir.join("colors.sh"), &sh)?;
+
+    // colors.Xresources
+    let mut xres = String::from("! X colors.\n! Generated by randpaper\n");
+    let _ = writeln!(xres, "*foreground:        {}", fg.hex());
+    let _ = writeln!(xres, "*background:        {}", bg.hex());
+    let _ = writeln!(xres, "*.foreground:       {}", fg.hex());
+    let _ = writeln!(xres, "*.background:       {}", bg.hex());
+    let _ = writeln!(xres, "*cursorColor:       {}", fg.hex());
+    let _ = writeln!(xres, "*.cursorColor:      {}", fg.hex());
+    for (i, c) in colors.iter().enumerate() {
+        let _ = writeln!(xres, "*.color{i}: {}", c.hex());
+        let _ = writeln!(xres, "*color{i}:  {}", c.hex());
+    }
+    atomic_write(&dir.join("colors.Xresources"), &xres)?;
+
+    // colors.css, with `\` and `"` escaped inside the quoted url()
+    let mut css = String::from("/* CSS variables\n   Generated by randpaper */\n:root {\n");
+    let css_path = wallpaper.replace('\\', r"\\").replace('"', r#"\""#);
+    let _ = writeln!(css, "        --wallpaper: url(\"{css_path}\");\n");
+    let _ = writeln!(css, "        /* Special */");
+    let _ = writeln!(css, "        --background: {};", bg.hex());
+    let _ = writeln!(css, "        --foreground: {};", fg.hex());
+    let _ = writeln!(css, "        --cursor: {};\n", fg.hex());
+    let _ = writeln!(css, "        /* Colors */");
+    for (i, c) in colors.iter().enumerate() {
+        let _ = writeln!(css, "        --color{i}: {};", c.hex());
+    }
+    css.push_str("}\n");
+    atomic_write(&dir.join("colors.css"), &css)?;
+
+    // Plain list (one color per line) and the current wallpaper path
+    let mut plain = String::new();
+    for c in colors {
+        let _ = writeln!(plain, "{}", c.hex());
+    }
+    atomic_write(&dir.join("colors"), &plain)?;
+    atomic_write(&dir.join("wal"), wallpaper.as_bytes())?;
+
+    log::info!("Updated pywal colors in {}", dir.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::StubDir;
+    use crate::theme::{Rgb, theme_from_palette};
+    use std::process::Command;
+
+    #[test]
+    fn quotes_in_the_wallpaper_name_are_escaped() {
+        let dir = StubDir::new();
+        let wallpaper = r#"/walls/it's $(touch pwned) `x` "y" \z.png"#;
+        let color = Rgb::from_hex("#336699").expect("valid hex");
+        let theme = theme_from_palette(Path::new(wallpaper), &[color]);
+        write_pywal(&dir.path("wal"), &theme).expect("write pywal");
+
+        let out = Command::new("sh")
+            .current_dir(dir.path(""))
+            .args(["-c", ". ./wal/colors.sh && printf '%s' \"$wallpaper\""])
+            .output()
+            .expect("run sh");
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        assert_eq!(String::from_utf8_lossy(&out.stdout), wallpaper);
+        assert!(!dir.path("pwned").exists());
+
+        let css = std::fs::read_to_string(dir.path("wal/colors.css")).expect("read colors.css");
+        let expected = r#"--wallpaper: url("/walls/it's $(touch pwned) `x` \"y\" \\z.png");"#;
+        assert!(css.contains(expected), "{css}");
+    }
+}
@@ -0,0 +1,282 @@
+use crate::cli::ReloadAction;
+use anyhow::{Context, bail};
+use std::path::Path;
+use std::process::Command;
+
+/// Placeholder in reload command arguments that is replaced with the output path.
+const PATH_PLACEHOLDER: &str = "{path}";
+
+/// Characters that make a kitty socket address a glob pattern rather than a literal path.
+const GLOB_CHARS: &[char] = &['*', '?', '['];
+
+/// Short name of an action, used in log records.
+const fn kind(action: &ReloadAction) -> &'static str {
+    match action {
+        ReloadAction::None => "none",
+        ReloadAction::Signal { .. } => "signal",
+        ReloadAction::Command { .. } => "command",
+        ReloadAction::Kitty { .. } => "kitty",
+        ReloadAction::GtkTheme => "gtk-theme",
+        ReloadAction::Neovim { .. } => "neovim",
+    }
+}
+
+/// Runs a command to completion, turning a non-zero exit into an error.
+fn run_status(cmd: &mut Command) -> anyhow::Result<()> {
+    let status = cmd
+        .status()
+        .with_context(|| format!("failed to run {}", cmd.get_program().display()))?;
+    if !status.success() {
+        bail!("{} exited with {status}", cmd.get_program().display());
+    }
+    Ok(())
+}
+
+/// Expands a glob pattern into the existing paths it matches.
+fn glob_paths(pattern: &str) -> anyhow::Result<Vec<String>> {
+    Ok(glob::glob(pattern)
+        .with_context(|| format!("invalid socket pattern: {pattern}"))?
+        .filter_map(Result::ok)
+        .map(|p| p.display().to_string())
+        .collect())
+}
+
+/// Runs `send` for every socket, so one stale socket left behind by a crashed
+/// instance cannot keep the others from reloading.
+///
+/// Each failure is logged; the action only fails if no socket could be reached.
+fn for_each_socket(
+    sockets: &[String],
+    send: impl Fn(&str) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let mut failed = 0;
+    for socket in sockets {
+        if let Err(e) = send(socket) {
+            failed += 1;
+            log::warn!(socket = socket.as_str(), error:% = format!("{e:#}"); "reload via socket failed");
+        }
+    }
+    if failed > 0 && failed == sockets.len() {
+        bail!("all {failed} socket(s) failed");
+    }
+    Ok(())
+}
+
+/// Neovim's default server sockets, `$XDG_RUNTIME_DIR/nvim.<pid>.<n>`.
+fn default_nvim_sockets() -> String {
+    let runtime = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".to_string());
+    format!("{runtime}/nvim.*")
+}
+
+/// Keys sent to Neovim: reload the scheme only where it is the active one.
+const NVIM_RELOAD_KEYS: &str =
+    "<Cmd>if get(g:, 'colors_name', '') ==# 'randpaper' | colorscheme randpaper | endif<CR>";
+
+/// Expands a kitty `--to` address into concrete socket addresses.
+///
+/// Only `unix:` paths containing glob characters are expanded; anything else
+/// (literal paths, abstract `unix:@name` sockets, `tcp:` addresses) is passed through.
+fn kitty_sockets(socket: &str) -> anyhow::Result<Vec<String>> {
+    let Some(pattern) = socket.strip_prefix("unix:") else {
+        return Ok(vec![socket.to_string()]);
+    };
+    if !pattern.contains(GLOB_CHARS) {
+        return Ok(vec![socket.to_string()]);
+    }
+
+    Ok(glob_paths(pattern)?
+        .into_iter()
+        .map(|p| format!("unix:{p}"))
+        .collect())
+}
+
+fn run_action(action: &ReloadAction, path: &Path) -> anyhow::Result<()> {
+    match action {
+        ReloadAction::None => Ok(()),
+        ReloadAction::Signal { signal, process } => {
+            // pkill exits 1 when nothing matched, which is not a failure for us
+            let status = Command::new("pkill")
+                .arg(format!("-{signal}"))
+                .args(["-x", process])
+                .status()
+                .context("failed to run pkill")?;
+            match status.code() {
+                Some(0 | 1) => Ok(()),
+                _ => bail!("pkill -{signal} -x {process} exited with {status}"),
+            }
+        }
+        ReloadAction::Command { argv } => {
+            let path = path.to_string_lossy();
+            let (program, args) = argv.split_first().context("reload command is empty")?;
+            run_status(
+                Command::new(program).args(args.iter().map(|a| a.replace(PATH_PLACEHOLDER, &path))),
+            )
+        }
+        ReloadAction::Kitty { socket } => {
+            let sockets = kitty_sockets(socket)?;
+            if sockets.is_empty() {
+                log::debug!("no kitty sockets match {socket}");
+            }
+            for_each_socket(&sockets, |to| {
+                run_status(
+                    Command::new("kitten")
+                        .args(["@", "--to", to, "set-colors", "--all", "--configured"])
+                        .arg(path),
+                )
+            })
+        }
+        ReloadAction::GtkTheme => {
+            super::toolkit::reload_gtk();
+            Ok(())
+        }
+        ReloadAction::Neovim { socket } => {
+            let pattern = socket.clone().unwrap_or_else(default_nvim_sockets);
+            let sockets = glob_paths(&pattern)?;
+            if sockets.is_empty() {
+                log::debug!("no neovim sockets match {pattern}");
+            }
+            for_each_socket(&sockets, |server| {
+                run_status(Command::new("nvim").args([
+                    "--server",
+                    server,
+                    "--remote-send",
+                    NVIM_RELOAD_KEYS,
+                ]))
+            })
+        }
+    }
+}
+
+/// Runs the reload action for one theme target and logs the outcome.
+///
+/// Reloads are best-effort: a failure is logged and never aborts the rotation.
+pub fn reload(name: &str, action: &ReloadAction, path: &Path) {
+    if matches!(action, ReloadAction::None) {
+        return;
+    }
+
+    let action_kind = kind(action);
+    match run_action(action, path) {
+        Ok(()) => log::info!(target_name = name, action = action_kind; "theme target reloaded"),
+        Err(e) => log::warn!(
+            target_name = name,
+            action = action_kind,
+            error:% = format!("{e:#}");
+            "theme target reload failed"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{Config, TargetConfig};
+    use crate::test_util::{StubDir, touch};
+    use figment::Figment;
+    use figment::providers::{Format, Serialized, Toml};
+    use std::cell::RefCell;
+
+    fn reload(toml: &str) -> Option<ReloadAction> {
+        Figment::from(Toml::string(&format!("enabled = true\nreload = {toml}")))
+            .extract::<TargetConfig>()
+            .ok()
+            .map(|target| target.reload)
+    }
+
+    #[test]
+    fn deserializes_every_reload_action() {
+        assert!(matches!(
+            reload(r#"{ type = "none" }"#),
+            Some(ReloadAction::None)
+        ));
+        assert!(matches!(
+            reload(r#"{ type = "signal", signal = "USR2", process = "waybar" }"#),
+            Some(ReloadAction::Signal { signal, process }) if signal == "USR2" && process == "waybar"
+        ));
+        assert!(matches!(
+            reload(r#"{ type = "command", argv = ["makoctl", "reload"] }"#),
+            Some(ReloadAction::Command { argv }) if argv == ["makoctl", "reload"]
+        ));
+        assert!(matches!(
+            reload(r#"{ type = "kitty", socket = "unix:/tmp/kitty-*" }"#),
+            Some(ReloadAction::Kitty { socket }) if socket == "unix:/tmp/kitty-*"
+        ));
+        assert!(matches!(
+            reload(r#"{ type = "gtk-theme" }"#),
+            Some(ReloadAction::GtkTheme)
+        ));
+        assert!(matches!(
+            reload(r#"{ type = "neovim" }"#),
+            Some(ReloadAction::Neovim { socket: None })
+        ));
+        assert!(matches!(
+            reload(r#"{ type = "neovim", socket = "/run/nvim.sock" }"#),
+            Some(ReloadAction::Neovim { socket: Some(s) }) if s == "/run/nvim.sock"
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_or_incomplete_reload_actions() {
+        assert!(reload(r#"{ type = "restart" }"#).is_none());
+        assert!(reload(r#"{ type = "signal", signal = "USR2" }"#).is_none());
+        assert!(reload(r#"{ signal = "USR2", process = "waybar" }"#).is_none());
+    }
+
+    #[test]
+    fn a_partial_target_table_keeps_the_default_reload() {
+        let config: Config = Figment::from(Serialized::defaults(Config::default()))
+            .merge(Toml::string("[theme.targets.waybar]\nenabled = false\n"))
+            .extract()
+            .expect("config");
+        let waybar = &config.theme.targets.waybar;
+        assert!(!waybar.enabled);
+        assert!(matches!(
+            &waybar.reload,
+            ReloadAction::Signal { process, .. } if process == "waybar"
+        ));
+    }
+
+    #[test]
+    fn expands_only_unix_socket_globs() {
+        let dir = StubDir::new();
+        touch(&dir.path("kitty-1"));
+        touch(&dir.path("kitty-2"));
+        let pattern = format!("unix:{}", dir.path("kitty-*").display());
+
+        let mut sockets = kitty_sockets(&pattern).expect("glob");
+        sockets.sort();
+        let expected = ["kitty-1", "kitty-2"].map(|n| format!("unix:{}", dir.path(n).display()));
+        assert_eq!(sockets, expected);
+
+        for literal in ["unix:/tmp/kitty", "unix:@kitty", "tcp:localhost:5000"] {
+            assert_eq!(kitty_sockets(literal).expect("literal"), [literal]);
+        }
+    }
+
+    #[test]
+    fn a_stale_socket_does_not_stop_the_others() {
+        let sockets = [
+            "unix:/tmp/kitty-1",
+            "unix:/tmp/kitty-stale",
+            "unix:/tmp/kitty-3",
+        ]
+        .map(String::from);
+        let reached = RefCell::new(Vec::new());
+        for_each_socket(&sockets, |to| {
+            if to.ends_with("stale") {
+                bail!("connection refused");
+            }
+            reached.borrow_mut().push(to.to_string());
+            Ok(())
+        })
+        .expect("one socket still reloaded");
+        assert_eq!(
+            reached.into_inner(),
+            ["unix:/tmp/kitty-1", "unix:/tmp/kitty-3"]
+        );
+
+        let err = for_each_socket(&sockets, |_| bail!("connection refused"))
+            .expect_err("every socket failed");
+        assert!(err.to_string().contains("all 3"), "{err:#}");
+    }
+}
@@ -0,0 +1,255 @@
+use super::{Rgb, Roles, Theme};
+use crate::cli::{ThemeConfig, ThemeSource};
+use anyhow::Context;
+use figment::Figment;
+use figment::providers::{Format, Toml, Yaml};
+use serde::Deserialize;
+use serde::de::IgnoredAny;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// One value in a scheme file: a color, a nested table (the newer `palette:`
+/// layout), or metadata we don't care about.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    Hex(String),
+    /// Unquoted all-digit colors (e.g. `base00: 282828`) are parsed by YAML as numbers.
+    Num(u64),
+    Table(BTreeMap<String, Self>),
+    Other(IgnoredAny),
+}
+
+/// Collects `baseXX` keys from either the classic flat layout or a nested `palette:` table.
+fn collect_bases(entries: &BTreeMap<String, Entry>, out: &mut BTreeMap<String, Rgb>) {
+    for (key, entry) in entries {
+        match entry {
+            Entry::Table(inner) if key == "palette" => collect_bases(inner, out),
+            Entry::Hex(s) if key.starts_with("base") => {
+                if let Some(c) = Rgb::from_hex(s) {
+                    out.insert(key.to_ascii_uppercase().replacen("BASE", "base", 1), c);
+                }
+            }
+            Entry::Num(n) if key.starts_with("base") => {
+                if let Some(c) = Rgb::from_hex(&format!("{n:06}")) {
+                    out.insert(key.to_ascii_uppercase().replacen("BASE", "base", 1), c);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Loads a base16 or base24 scheme and maps it onto a [`Theme`].
+///
+/// Files ending in `.toml` are read as TOML, everything else as YAML.
+///
+/// Roles follow the base16 styling guidelines (`base00` background, `base05`
+/// foreground, `base0D` accent, `base0A` warn, `base0B` ok). Terminal colors use
+/// the standard base16-shell mapping; base24 schemes supply their own bright
+/// colors (`base12`-`base17`), base16 schemes reuse the normal ones.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or any `base00`-`base0F` color is missing.
+pub fn load_scheme(scheme_path: &Path, wallpaper: &Path) -> anyhow::Result<Theme> {
+    let raw = std::fs::read_to_string(scheme_path)
+        .with_context(|| format!("failed to read scheme: {}", scheme_path.display()))?;
+    let is_toml = scheme_path.extension().is_some_and(|ext| ext == "toml");
+    let figment = if is_toml {
+        Figment::from(Toml::string(&raw))
+    } else {
+        Figment::from(Yaml::string(&raw))
+    };
+    let entries: BTreeMap<String, Entry> = figment
+        .extract()
+        .with_context(|| format!("invalid scheme: {}", scheme_path.display()))?;
+
+    let mut bases = BTreeMap::new();
+    collect_bases(&entries, &mut bases);
+
+    let base = |key: &str| {
+        bases
+            .get(key)
+            .copied()
+            .with_context(|| format!("{key} missing from scheme {}", scheme_path.display()))
+    };
+    // base24 brights fall back to their base16 counterparts
+    let bright =
+        |key: &str, fallback: &str| bases.get(key).copied().map_or_else(|| base(fallback), Ok);
+
+    let colors = vec![
+        base("base00")?,
+        base("base08")?,
+        base("base0B")?,
+        base("base0A")?,
+        base("base0D")?,
+        base("base0E")?,
+        base("base0C")?,
+        base("base05")?,
+        base("base03")?,
+        bright("base12", "base08")?,
+        bright("base14", "base0B")?,
+        bright("base13", "base0A")?,
+        bright("base16", "base0D")?,
+        bright("base17", "base0E")?,
+        bright("base15", "base0C")?,
+        base("base07")?,
+    ];
+
+    Ok(Theme {
+        wallpaper: wallpaper.to_path_buf(),
+        roles: Roles {
+            bg: base("base00")?,
+            fg: base("base05")?,
+            accent: base("base0D")?,
+            warn: base("base0A")?,
+            ok: base("base0B")?,
+        },
+        colors,
+    })
+}
+
+/// Picks the theme source for a wallpaper.
+///
+/// The longest `[theme.directories]` entry containing the image wins; otherwise
+/// `theme.source` applies.
+pub fn source_for<'a>(config: &'a ThemeConfig, image: &Path) -> &'a ThemeSource {
+    config
+        .directories
+        .iter()
+        .map(|(dir, source)| (super::expand_tilde(dir), source))
+        .filter(|(dir, _)| image.starts_with(dir))
+        .max_by_key(|(dir, _)| dir.components().count())
+        .map_or(&config.source, |(_, source)| source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::StubDir;
+    use std::fmt::Write;
+    use std::path::PathBuf;
+
+    const BASE16: &str = r#"
+scheme: "Test"
+author: "randpaper"
+base00: "101010"
+base01: "111111"
+base02: "121212"
+base03: "131313"
+base04: "141414"
+base05: "151515"
+base06: "161616"
+base07: "171717"
+base08: "181818"
+base09: "191919"
+base0A: "1a1a1a"
+base0B: "1b1b1b"
+base0C: "1c1c1c"
+base0D: "1d1d1d"
+base0E: "1e1e1e"
+base0F: "1f1f1f"
+"#;
+
+    fn hex(s: &str) -> Rgb {
+        Rgb::from_hex(s).expect("valid hex")
+    }
+
+    fn load(dir: &StubDir, name: &str, contents: &str) -> anyhow::Result<Theme> {
+        let path = dir.path(name);
+        std::fs::write(&path, contents).expect("write scheme");
+        load_scheme(&path, Path::new("wall.png"))
+    }
+
+    #[test]
+    fn maps_a_flat_base16_scheme() {
+        let dir = StubDir::new();
+        let theme = load(&dir, "flat.yaml", BASE16).expect("scheme");
+
+        assert_eq!(theme.roles.bg, hex("101010"));
+        assert_eq!(theme.roles.fg, hex("151515"));
+        assert_eq!(theme.roles.accent, hex("1d1d1d"));
+        assert_eq!(theme.roles.warn, hex("1a1a1a"));
+        assert_eq!(theme.roles.ok, hex("1b1b1b"));
+
+        let expected: Vec<Rgb> = [
+            "10", "18", "1b", "1a", "1d", "1e", "1c", "15", //
+            "13", "18", "1b", "1a", "1d", "1e", "1c", "17",
+        ]
+        .iter()
+        .map(|b| hex(&b.repeat(3)))
+        .collect();
+        assert_eq!(theme.colors, expected);
+        assert_eq!(theme.wallpaper, PathBuf::from("wall.png"));
+    }
+
+    #[test]
+    fn base24_brights_replace_the_reused_normals() {
+        let dir = StubDir::new();
+        let mut yaml = String::from("palette:\n");
+        for line in BASE16.lines().filter(|l| l.starts_with("base")) {
+            let _ = writeln!(yaml, "  {line}");
+        }
+        for (key, value) in [
+            ("base12", "a2a2a2"),
+            ("base13", "a3a3a3"),
+            ("base14", "a4a4a4"),
+            ("base15", "a5a5a5"),
+            ("base16", "a6a6a6"),
+            ("base17", "a7a7a7"),
+        ] {
+            let _ = writeln!(yaml, "  {key}: \"{value}\"");
+        }
+        let theme = load(&dir, "nested.yaml", &yaml).expect("scheme");
+
+        let brights: Vec<Rgb> = ["a2", "a4", "a3", "a6", "a7", "a5"]
+            .iter()
+            .map(|b| hex(&b.repeat(3)))
+            .collect();
+        assert_eq!(theme.colors[9..15], brights);
+        assert_eq!(theme.colors[15], hex("171717"));
+    }
+
+    #[test]
+    fn reads_toml_schemes_and_unquoted_yaml_numbers() {
+        let dir = StubDir::new();
+        let toml = BASE16.replace(": ", " = ");
+        let from_toml = load(&dir, "scheme.toml", &toml).expect("toml scheme");
+        let from_yaml = load(&dir, "scheme.yaml", BASE16).expect("yaml scheme");
+        assert_eq!(from_toml.colors, from_yaml.colors);
+
+        let unquoted = BASE16.replace("base00: \"101010\"", "base00: 101010");
+        let theme = load(&dir, "unquoted.yaml", &unquoted).expect("scheme");
+        assert_eq!(theme.roles.bg, hex("101010"));
+    }
+
+    #[test]
+    fn reports_missing_base16_colors() {
+        let dir = StubDir::new();
+        let partial = BASE16.replace("base0D: \"1d1d1d\"\n", "");
+        let err = load(&dir, "partial.yaml", &partial).expect_err("base0D missing");
+        assert!(err.to_string().contains("base0D missing"), "{err:#}");
+    }
+
+    #[test]
+    fn the_most_specific_directory_picks_the_source() {
+        let scheme = |name: &str| ThemeSource::Scheme(PathBuf::from(name));
+        let mut config = ThemeConfig::default();
+        config
+            .directories
+            .insert("/walls".into(), scheme("walls.yaml"));
+        config
+            .directories
+            .insert("/walls/nord".into(), scheme("nord.yaml"));
+
+        let source = |image: &str| source_for(&config, Path::new(image));
+        assert!(
+            matches!(source("/walls/nord/a.png"), ThemeSource::Scheme(p) if p.ends_with("nord.yaml"))
+        );
+        assert!(
+            matches!(source("/walls/b.png"), ThemeSource::Scheme(p) if p.ends_with("walls.yaml"))
+        );
+        assert!(matches!(source("/other/c.png"), ThemeSource::Extract));
+    }
+}
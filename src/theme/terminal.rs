@@ -0,0 +1,109 @@
+use super::{Roles, Theme, atomic_write};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Writes a Ghostty `config-file` include with the palette and background/foreground.
+pub fn write_ghostty(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles { bg, fg, .. } = theme.roles;
+    let mut ghostty = String::from("# Auto-generated by randpaper\n");
+    for (i, color) in theme.colors.iter().enumerate() {
+        let _ = writeln!(ghostty, "palette = {i}={}", color.hex());
+    }
+    let _ = writeln!(ghostty, "background = {}", bg.hex());
+    let _ = writeln!(ghostty, "foreground = {}", fg.hex());
+    atomic_write(path, &ghostty)
+}
+
+/// Writes a Kitty include (also used by `kitten @ set-colors --configured`).
+pub fn write_kitty(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles { bg, fg, .. } = theme.roles;
+    let mut kitty = String::from("# Auto-generated by randpaper\n");
+    for (i, color) in theme.colors.iter().enumerate() {
+        let _ = writeln!(kitty, "color{i} {}", color.hex());
+    }
+    let _ = writeln!(kitty, "background {}", bg.hex());
+    let _ = writeln!(kitty, "foreground {}", fg.hex());
+    atomic_write(path, &kitty)
+}
+
+/// Writes a Foot `[colors]` include. Foot expects hex values without the leading `#`.
+pub fn write_foot(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles { bg, fg, .. } = theme.roles;
+    let bare = |c: super::Rgb| c.hex().trim_start_matches('#').to_string();
+    let mut foot = String::from("# Auto-generated by randpaper\n[colors]\n");
+    for (i, color) in theme.colors.iter().enumerate() {
+        if i < 8 {
+            let _ = writeln!(foot, "regular{i}={}", bare(*color));
+        } else {
+            let _ = writeln!(foot, "bright{}={}", i - 8, bare(*color));
+        }
+    }
+    let _ = writeln!(foot, "background={}", bare(bg));
+    let _ = writeln!(foot, "foreground={}", bare(fg));
+    atomic_write(path, &foot)
+}
+
+/// ANSI color names in `colorN` order, as Alacritty spells them.
+const ANSI_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// Writes an Alacritty import (`general.import`); Alacritty live-reloads imported files.
+pub fn write_alacritty(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles { bg, fg, .. } = theme.roles;
+    let mut alacritty = String::from("# Auto-generated by randpaper\n[colors.primary]\n");
+    let _ = writeln!(alacritty, "background = \"{}\"", bg.hex());
+    let _ = writeln!(alacritty, "foreground = \"{}\"", fg.hex());
+    for (section, colors) in [
+        ("normal", &theme.colors[..8]),
+        ("bright", &theme.colors[8..]),
+    ] {
+        let _ = writeln!(alacritty, "\n[colors.{section}]");
+        for (name, color) in ANSI_NAMES.iter().zip(colors) {
+            let _ = writeln!(alacritty, "{name} = \"{}\"", color.hex());
+        }
+    }
+    atomic_write(path, &alacritty)
+}
+
+/// Writes a `wezterm` color scheme named `randpaper` (`config.color_scheme = "randpaper"`).
+pub fn write_wezterm(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles { bg, fg, accent, .. } = theme.roles;
+    let list = |colors: &[super::Rgb]| {
+        colors
+            .iter()
+            .map(|c| format!("\"{}\"", c.hex()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut wezterm = String::from("# Auto-generated by randpaper\n[colors]\n");
+    for (key, color) in [
+        ("foreground", fg),
+        ("background", bg),
+        ("cursor_bg", fg),
+        ("cursor_border", fg),
+        ("cursor_fg", bg),
+        ("selection_bg", accent),
+        ("selection_fg", bg),
+    ] {
+        let _ = writeln!(wezterm, "{key} = \"{}\"", color.hex());
+    }
+    let _ = writeln!(wezterm, "ansi = [{}]", list(&theme.colors[..8]));
+    let _ = writeln!(wezterm, "brights = [{}]", list(&theme.colors[8..]));
+    wezterm.push_str("\n[metadata]\nname = \"randpaper\"\n");
+    atomic_write(path, &wezterm)
+}
+
+/// Writes X resources for st, urxvt, xterm and other X11 terminals.
+pub fn write_xresources(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles { bg, fg, .. } = theme.roles;
+    let mut xresources = String::from("! Auto-generated by randpaper\n");
+    let _ = writeln!(xresources, "*.background: {}", bg.hex());
+    let _ = writeln!(xresources, "*.foreground: {}", fg.hex());
+    let _ = writeln!(xresources, "*.cursorColor: {}", fg.hex());
+    for (i, color) in theme.colors.iter().enumerate() {
+        let _ = writeln!(xresources, "*.color{i}: {}", color.hex());
+    }
+    atomic_write(path, &xresources)
+}
@@ -0,0 +1,207 @@
+use super::{Rgb, Roles, Theme, atomic_write};
+use std::fmt::Write;
+use std::path::Path;
+use std::process::Command;
+
+/// Generates a GTK 3/4 color override.
+///
+/// Defines both the libadwaita named colors (`window_bg_color`, `accent_color`, ...)
+/// used by GTK 4 apps and the legacy `theme_*` names used by most GTK 3 themes.
+/// Users `@import` it from `~/.config/gtk-3.0/gtk.css` and `~/.config/gtk-4.0/gtk.css`.
+pub fn write_gtk_css(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles {
+        bg,
+        fg,
+        accent,
+        warn,
+        ok,
+    } = theme.roles;
+    // Raised surfaces (cards, popovers, headerbars) sit slightly above the window background
+    let raised = bg.mix(fg, 0.06);
+    let sidebar = bg.mix(fg, 0.03);
+
+    let colors = [
+        // libadwaita / GTK 4
+        ("accent_color", accent),
+        ("accent_bg_color", accent),
+        ("accent_fg_color", bg),
+        ("destructive_color", warn),
+        ("destructive_bg_color", warn),
+        ("destructive_fg_color", bg),
+        ("success_color", ok),
+        ("success_bg_color", ok),
+        ("success_fg_color", bg),
+        ("warning_color", warn),
+        ("warning_bg_color", warn),
+        ("warning_fg_color", bg),
+        ("error_color", warn),
+        ("error_bg_color", warn),
+        ("error_fg_color", bg),
+        ("window_bg_color", bg),
+        ("window_fg_color", fg),
+        ("view_bg_color", bg),
+        ("view_fg_color", fg),
+        ("headerbar_bg_color", raised),
+        ("headerbar_fg_color", fg),
+        ("headerbar_backdrop_color", bg),
+        ("sidebar_bg_color", sidebar),
+        ("sidebar_fg_color", fg),
+        ("card_bg_color", raised),
+        ("card_fg_color", fg),
+        ("dialog_bg_color", raised),
+        ("dialog_fg_color", fg),
+        ("popover_bg_color", raised),
+        ("popover_fg_color", fg),
+        // GTK 3 themes
+        ("theme_bg_color", bg),
+        ("theme_fg_color", fg),
+        ("theme_base_color", bg),
+        ("theme_text_color", fg),
+        ("theme_selected_bg_color", accent),
+        ("theme_selected_fg_color", bg),
+        ("insensitive_bg_color", bg),
+        ("insensitive_fg_color", bg.mix(fg, 0.5)),
+        ("borders", raised),
+    ];
+
+    let mut css = String::from("/* auto-generated by randpaper */\n");
+    for (name, color) in colors {
+        let _ = writeln!(css, "@define-color {name} {};", color.hex());
+    }
+
+    atomic_write(path, &css)
+}
+
+/// Formats a color as the `r,g,b` triple used by KDE `.colors` files.
+fn kde(c: Rgb) -> String {
+    format!("{},{},{}", c.r, c.g, c.b)
+}
+
+/// Formats a color as the `#aarrggbb` value used by qt5ct/qt6ct palettes.
+fn qtct(c: Rgb) -> String {
+    format!("#ff{}", c.hex().trim_start_matches('#'))
+}
+
+/// Generates a KDE `randpaper.colors` scheme and a qt5ct/qt6ct `qtct.conf` palette.
+///
+/// The KDE scheme goes in `~/.local/share/color-schemes/`; the qtct palette is
+/// selected from qt5ct/qt6ct's "Color scheme" page (`~/.config/qt6ct/colors/`).
+pub fn write_qt_schemes(dir: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles {
+        bg,
+        fg,
+        accent,
+        warn,
+        ok,
+    } = theme.roles;
+    let alt = bg.mix(fg, 0.04);
+    let button = bg.mix(fg, 0.08);
+    let inactive = bg.mix(fg, 0.5);
+
+    // KDE color scheme
+    let mut colors = String::from("# auto-generated by randpaper\n");
+    let _ = writeln!(colors, "[General]\nColorScheme=randpaper\nName=randpaper\n");
+    for (section, normal, alternate) in [
+        ("Window", bg, alt),
+        ("View", bg, alt),
+        ("Button", button, alt),
+        ("Tooltip", button, alt),
+        ("Complementary", bg, alt),
+        ("Header", bg, alt),
+    ] {
+        let _ = writeln!(colors, "[Colors:{section}]");
+        let _ = writeln!(colors, "BackgroundNormal={}", kde(normal));
+        let _ = writeln!(colors, "BackgroundAlternate={}", kde(alternate));
+        let _ = writeln!(colors, "ForegroundNormal={}", kde(fg));
+        let _ = writeln!(colors, "ForegroundInactive={}", kde(inactive));
+        let _ = writeln!(colors, "ForegroundActive={}", kde(accent));
+        let _ = writeln!(colors, "ForegroundLink={}", kde(accent));
+        let _ = writeln!(colors, "ForegroundVisited={}", kde(accent));
+        let _ = writeln!(colors, "ForegroundNegative={}", kde(warn));
+        let _ = writeln!(colors, "ForegroundNeutral={}", kde(warn));
+        let _ = writeln!(colors, "ForegroundPositive={}", kde(ok));
+        let _ = writeln!(colors, "DecorationFocus={}", kde(accent));
+        let _ = writeln!(colors, "DecorationHover={}\n", kde(accent));
+    }
+    let _ = writeln!(colors, "[Colors:Selection]");
+    let _ = writeln!(colors, "BackgroundNormal={}", kde(accent));
+    let _ = writeln!(colors, "BackgroundAlternate={}", kde(accent));
+    let _ = writeln!(colors, "ForegroundNormal={}", kde(bg));
+    let _ = writeln!(colors, "ForegroundInactive={}", kde(bg));
+    let _ = writeln!(colors, "DecorationFocus={}", kde(accent));
+    let _ = writeln!(colors, "DecorationHover={}\n", kde(accent));
+    let _ = writeln!(colors, "[WM]");
+    let _ = writeln!(colors, "activeBackground={}", kde(bg));
+    let _ = writeln!(colors, "activeForeground={}", kde(fg));
+    let _ = writeln!(colors, "inactiveBackground={}", kde(bg));
+    let _ = writeln!(colors, "inactiveForeground={}", kde(inactive));
+    atomic_write(&dir.join("randpaper.colors"), &colors)?;
+
+    // qt5ct/qt6ct palette, one entry per QPalette::ColorRole in declaration order:
+    // WindowText, Button, Light, Midlight, Dark, Mid, Text, BrightText, ButtonText,
+    // Base, Window, Shadow, Highlight, HighlightedText, Link, LinkVisited,
+    // AlternateBase, NoRole, ToolTipBase, ToolTipText, PlaceholderText
+    let palette = |text: Rgb| {
+        [
+            text,
+            button,
+            bg.mix(fg, 0.2),
+            bg.mix(fg, 0.12),
+            bg.mix(Rgb { r: 0, g: 0, b: 0 }, 0.4),
+            bg.mix(fg, 0.1),
+            text,
+            fg,
+            text,
+            bg,
+            bg,
+            Rgb { r: 0, g: 0, b: 0 },
+            accent,
+            bg,
+            accent,
+            accent,
+            alt,
+            bg,
+            button,
+            fg,
+            inactive,
+        ]
+        .into_iter()
+        .map(qtct)
+        .collect::<Vec<_>>()
+        .join(", ")
+    };
+    let mut qt = String::from("# auto-generated by randpaper\n[ColorScheme]\n");
+    let _ = writeln!(qt, "active_colors={}", palette(fg));
+    let _ = writeln!(qt, "disabled_colors={}", palette(inactive));
+    let _ = writeln!(qt, "inactive_colors={}", palette(fg));
+    atomic_write(&dir.join("qtct.conf"), &qt)
+}
+
+/// Forces running GTK apps to re-read `gtk.css` by briefly switching the
+/// `gtk-theme` setting away and back again.
+pub fn reload_gtk() {
+    let key = ["org.gnome.desktop.interface", "gtk-theme"];
+    let Ok(out) = Command::new("gsettings").arg("get").args(key).output() else {
+        log::warn!("gsettings not found; skipping GTK reload");
+        return;
+    };
+    let current = String::from_utf8_lossy(&out.stdout)
+        .trim()
+        .trim_matches('\'')
+        .to_string();
+    if !out.status.success() || current.is_empty() {
+        return;
+    }
+
+    let _ = Command::new("gsettings")
+        .arg("set")
+        .args(key)
+        .arg("")
+        .status();
+    let result = Command::new("gsettings")
+        .arg("set")
+        .args(key)
+        .arg(&current)
+        .status();
+    log::info!("GTK reload result: {result:?}");
+}
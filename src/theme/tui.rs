@@ -0,0 +1,345 @@
+use super::{Rgb, Roles, Theme, atomic_write};
+use anyhow::Context;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Name used for the Neovim colorscheme and the starship palette.
+pub const SCHEME_NAME: &str = "randpaper";
+
+/// Writes a Neovim Lua colorscheme (`colors/randpaper.lua`).
+///
+/// Only roles are used so the highlight groups stay readable on any wallpaper;
+/// the 16-color palette goes to `:terminal` via `terminal_color_N`.
+pub fn write_neovim(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles {
+        bg,
+        fg,
+        accent,
+        warn,
+        ok,
+    } = theme.roles;
+    let surface = bg.mix(fg, 0.08);
+    let selection = bg.mix(accent, 0.3);
+    let muted = bg.mix(fg, 0.45);
+    let soft = fg.mix(accent, 0.5);
+
+    let mut lua = String::from("-- Auto-generated by randpaper\n");
+    lua.push_str("vim.cmd(\"highlight clear\")\n");
+    let _ = writeln!(lua, "vim.g.colors_name = \"{SCHEME_NAME}\"\n");
+    lua.push_str("local hl = function(group, spec) vim.api.nvim_set_hl(0, group, spec) end\n");
+
+    let groups: [(&str, Option<Rgb>, Option<Rgb>); 37] = [
+        ("Normal", Some(fg), Some(bg)),
+        ("NormalFloat", Some(fg), Some(surface)),
+        ("FloatBorder", Some(accent), Some(surface)),
+        ("CursorLine", None, Some(surface)),
+        ("CursorLineNr", Some(accent), None),
+        ("LineNr", Some(muted), None),
+        ("SignColumn", None, Some(bg)),
+        ("Visual", None, Some(selection)),
+        ("Search", Some(bg), Some(warn)),
+        ("IncSearch", Some(bg), Some(accent)),
+        ("MatchParen", Some(accent), Some(surface)),
+        ("StatusLine", Some(fg), Some(surface)),
+        ("StatusLineNC", Some(muted), Some(surface)),
+        ("WinSeparator", Some(surface), None),
+        ("Pmenu", Some(fg), Some(surface)),
+        ("PmenuSel", Some(bg), Some(accent)),
+        ("Title", Some(accent), None),
+        ("Directory", Some(accent), None),
+        ("Comment", Some(muted), None),
+        ("Constant", Some(warn), None),
+        ("String", Some(ok), None),
+        ("Identifier", Some(fg), None),
+        ("Function", Some(soft), None),
+        ("Statement", Some(accent), None),
+        ("Type", Some(soft), None),
+        ("PreProc", Some(warn), None),
+        ("Special", Some(accent), None),
+        ("Error", Some(warn), None),
+        ("ErrorMsg", Some(warn), None),
+        ("WarningMsg", Some(warn), None),
+        ("DiagnosticError", Some(warn), None),
+        ("DiagnosticWarn", Some(warn), None),
+        ("DiagnosticInfo", Some(accent), None),
+        ("DiagnosticHint", Some(muted), None),
+        ("DiagnosticOk", Some(ok), None),
+        ("DiffAdd", Some(ok), None),
+        ("DiffDelete", Some(warn), None),
+    ];
+    for (group, fg, bg) in groups {
+        let mut spec = Vec::new();
+        if let Some(c) = fg {
+            spec.push(format!("fg = \"{}\"", c.hex()));
+        }
+        if let Some(c) = bg {
+            spec.push(format!("bg = \"{}\"", c.hex()));
+        }
+        let _ = writeln!(lua, "hl(\"{group}\", {{ {} }})", spec.join(", "));
+    }
+
+    lua.push('\n');
+    for (i, color) in theme.colors.iter().enumerate() {
+        let _ = writeln!(lua, "vim.g.terminal_color_{i} = \"{}\"", color.hex());
+    }
+    atomic_write(path, &lua)
+}
+
+/// Writes tmux status, pane and message styles (`source-file <path>`).
+pub fn write_tmux(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles { bg, fg, accent, .. } = theme.roles;
+    let surface = bg.mix(fg, 0.08);
+
+    let mut conf = String::from("# Auto-generated by randpaper\n");
+    for (option, style) in [
+        (
+            "status-style",
+            format!("bg={},fg={}", surface.hex(), fg.hex()),
+        ),
+        (
+            "window-status-current-style",
+            format!("bg={},fg={},bold", accent.hex(), bg.hex()),
+        ),
+        ("pane-border-style", format!("fg={}", surface.hex())),
+        ("pane-active-border-style", format!("fg={}", accent.hex())),
+        (
+            "message-style",
+            format!("bg={},fg={}", accent.hex(), bg.hex()),
+        ),
+        ("mode-style", format!("bg={},fg={}", accent.hex(), bg.hex())),
+    ] {
+        let _ = writeln!(conf, "set -g {option} \"{style}\"");
+    }
+    atomic_write(path, &conf)
+}
+
+/// Writes a btop theme (`color_theme = "<path>"` in `btop.conf`).
+pub fn write_btop(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles {
+        bg,
+        fg,
+        accent,
+        warn,
+        ok,
+    } = theme.roles;
+    let surface = bg.mix(fg, 0.08);
+    let muted = bg.mix(fg, 0.45);
+
+    let mut conf = String::from("# Auto-generated by randpaper\n");
+    let keys = [
+        ("main_bg", bg),
+        ("main_fg", fg),
+        ("title", fg),
+        ("hi_fg", accent),
+        ("selected_bg", surface),
+        ("selected_fg", accent),
+        ("inactive_fg", muted),
+        ("graph_text", fg),
+        ("meter_bg", surface),
+        ("proc_misc", accent),
+        ("cpu_box", accent),
+        ("mem_box", accent),
+        ("net_box", accent),
+        ("proc_box", accent),
+        ("div_line", surface),
+    ];
+    for (key, color) in keys {
+        let _ = writeln!(conf, "theme[{key}]=\"{}\"", color.hex());
+    }
+    // Gradients run from calm to alarming: ok -> accent -> warn
+    for prefix in [
+        "temp",
+        "cpu",
+        "free",
+        "cached",
+        "available",
+        "used",
+        "download",
+        "upload",
+        "process",
+    ] {
+        let _ = writeln!(conf, "theme[{prefix}_start]=\"{}\"", ok.hex());
+        let _ = writeln!(conf, "theme[{prefix}_mid]=\"{}\"", accent.hex());
+        let _ = writeln!(conf, "theme[{prefix}_end]=\"{}\"", warn.hex());
+    }
+    atomic_write(path, &conf)
+}
+
+/// Writes a shell snippet that adds the theme to `FZF_DEFAULT_OPTS`.
+pub fn write_fzf(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let Roles {
+        bg,
+        fg,
+        accent,
+        warn,
+        ok,
+    } = theme.roles;
+    let surface = bg.mix(fg, 0.08);
+
+    let colors = [
+        ("bg", bg),
+        ("fg", fg),
+        ("hl", accent),
+        ("bg+", surface),
+        ("fg+", fg),
+        ("hl+", accent),
+        ("info", ok),
+        ("prompt", accent),
+        ("pointer", accent),
+        ("marker", ok),
+        ("spinner", warn),
+        ("header", accent),
+        ("border", surface),
+    ]
+    .map(|(key, color)| format!("{key}:{}", color.hex()))
+    .join(",");
+
+    let sh = format!(
+        "# Auto-generated by randpaper\nexport FZF_DEFAULT_OPTS=\"$FZF_DEFAULT_OPTS --color={colors}\"\n"
+    );
+    atomic_write(path, &sh)
+}
+
+/// Whether `line` is a TOML table header, and if so whether it is exactly `header`.
+///
+/// Trailing whitespace and comments are ignored, so `[palettes.randpaper] # old`
+/// matches but `[palettes.randpaper_dark]` does not.
+fn table_header(line: &str, header: &str) -> Option<bool> {
+    let trimmed = line.trim_start();
+    if !trimmed.starts_with('[') {
+        return None;
+    }
+    let code = trimmed.split_once('#').map_or(trimmed, |(code, _)| code);
+    Some(code.trim_end() == header)
+}
+
+/// Follows `path` to the file it points at, so a `starship.toml` symlinked by
+/// home-manager or stow is written through instead of replaced.
+fn resolve_symlinks(path: &Path) -> anyhow::Result<PathBuf> {
+    match std::fs::canonicalize(path) {
+        Ok(target) => Ok(target),
+        // A missing file is created, but a dangling symlink is left alone
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && path.symlink_metadata().is_err() => {
+            Ok(path.to_path_buf())
+        }
+        Err(e) => Err(e).with_context(|| format!("failed to resolve {}", path.display())),
+    }
+}
+
+/// Updates the `[palettes.randpaper]` table in `starship.toml` in place.
+///
+/// starship has no include mechanism, so any old copy of the table is removed
+/// and the new one appended; the rest of the file, comments included, is left
+/// untouched. Select it with `palette = "randpaper"`.
+pub fn write_starship(path: &Path, theme: &Theme) -> anyhow::Result<()> {
+    let path = &resolve_symlinks(path)?;
+    let Roles {
+        bg,
+        fg,
+        accent,
+        warn,
+        ok,
+    } = theme.roles;
+    let header = format!("[palettes.{SCHEME_NAME}]");
+
+    let existing = match std::fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+    };
+
+    // Keep every line outside our table; the table ends at the next header
+    let mut kept = String::new();
+    let mut in_table = false;
+    for line in existing.lines() {
+        if let Some(ours) = table_header(line, &header) {
+            in_table = ours;
+        }
+        if !in_table {
+            kept.push_str(line);
+            kept.push('\n');
+        }
+    }
+
+    let mut table = format!("{header}\n");
+    for (name, color) in [
+        ("bg", bg),
+        ("fg", fg),
+        ("accent", accent),
+        ("warn", warn),
+        ("ok", ok),
+    ] {
+        let _ = writeln!(table, "{name} = \"{}\"", color.hex());
+    }
+    for (i, color) in theme.colors.iter().enumerate() {
+        let _ = writeln!(table, "color{i} = \"{}\"", color.hex());
+    }
+
+    let kept = kept.trim_end();
+    let out = if kept.is_empty() {
+        table
+    } else {
+        format!("{kept}\n\n{table}")
+    };
+    atomic_write(path, &out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::StubDir;
+    use crate::theme::theme_from_palette;
+
+    fn theme(hex: &str) -> Theme {
+        let color = Rgb::from_hex(hex).expect("valid hex");
+        theme_from_palette(Path::new("wall.png"), &[color])
+    }
+
+    const USER_CONFIG: &str = "# my prompt\nformat = \"$all\"\npalette = \"randpaper\"\n\n[palettes.randpaper_dark]\nbg = \"#000000\"\n\n[character]\nsuccess_symbol = \"[>](bold accent)\"\n";
+
+    #[test]
+    fn starship_replaces_only_its_own_table() {
+        let dir = StubDir::new();
+        let path = dir.path("starship.toml");
+        std::fs::write(&path, USER_CONFIG).expect("write config");
+
+        write_starship(&path, &theme("#112233")).expect("first write");
+        write_starship(&path, &theme("#445566")).expect("second write");
+
+        let out = std::fs::read_to_string(&path).expect("read config");
+        assert!(out.starts_with(USER_CONFIG.trim_end()), "{out}");
+        assert_eq!(out.matches("[palettes.randpaper]").count(), 1, "{out}");
+        assert!(out.contains("color0 = \"#445566\""), "{out}");
+        assert!(!out.contains("#112233"), "{out}");
+    }
+
+    #[test]
+    fn starship_header_ignores_trailing_comments() {
+        assert_eq!(
+            table_header("[palettes.randpaper]  # managed", "[palettes.randpaper]"),
+            Some(true)
+        );
+        assert_eq!(
+            table_header("[palettes.randpaper-old]", "[palettes.randpaper]"),
+            Some(false)
+        );
+        assert_eq!(table_header("bg = \"#000\"", "[palettes.randpaper]"), None);
+    }
+
+    #[test]
+    fn starship_writes_through_symlinks() {
+        let dir = StubDir::new();
+        let target = dir.path("dotfiles-starship.toml");
+        let link = dir.path("starship.toml");
+        std::fs::write(&target, USER_CONFIG).expect("write config");
+        std::os::unix::fs::symlink(&target, &link).expect("symlink");
+
+        write_starship(&link, &theme("#112233")).expect("write");
+
+        let meta = link.symlink_metadata().expect("link metadata");
+        assert!(meta.file_type().is_symlink());
+        let out = std::fs::read_to_string(&target).expect("read target");
+        assert!(out.starts_with(USER_CONFIG.trim_end()), "{out}");
+        assert!(out.contains("[palettes.randpaper]"), "{out}");
+    }
+}
@@ -0,0 +1,107 @@
+use super::oklab::Oklab;
+use super::{Rgb, Roles};
+use crate::cli::TuningConfig;
+
+/// Below this chroma a color is treated as gray: its hue is noise, so
+/// `min_chroma` leaves it alone rather than tinting it a random color.
+const NEUTRAL_CHROMA: f32 = 0.02;
+
+/// Adjusts one color's chroma and lightness in `OKLCh`.
+fn tune_color(color: Rgb, tuning: &TuningConfig) -> Rgb {
+    let lab = Oklab::from(color);
+    let [min_l, max_l] = tuning.brightness_range;
+
+    let mut chroma = lab.chroma() * tuning.saturation_boost.max(0.0);
+    if lab.chroma() >= NEUTRAL_CHROMA {
+        chroma = chroma.max(tuning.min_chroma);
+    }
+    let l = lab.l.max(min_l).min(max_l);
+
+    Oklab::from_lch(l, chroma, lab.hue()).into()
+}
+
+/// Applies `saturation_boost`, `min_chroma` and `brightness_range` to an
+/// extracted palette, before roles are picked from it.
+pub fn palette(colors: &[Rgb], tuning: &TuningConfig) -> Vec<Rgb> {
+    if tuning.is_identity() {
+        return colors.to_vec();
+    }
+    colors.iter().map(|&c| tune_color(c, tuning)).collect()
+}
+
+/// Rotates the accent role's hue by `accent_hue_shift` degrees.
+pub fn accent(roles: &mut Roles, tuning: &TuningConfig) {
+    if tuning.accent_hue_shift.abs() < f32::EPSILON {
+        return;
+    }
+    let lab = Oklab::from(roles.accent);
+    let hue = lab.hue() + tuning.accent_hue_shift.to_radians();
+    roles.accent = Oklab::from_lch(lab.l, lab.chroma(), hue).into();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Rgb {
+        Rgb::from_hex(s).expect("valid hex")
+    }
+
+    fn tuning(edit: impl FnOnce(&mut TuningConfig)) -> TuningConfig {
+        let mut tuning = TuningConfig::default();
+        edit(&mut tuning);
+        tuning
+    }
+
+    #[test]
+    fn the_default_tuning_leaves_palettes_alone() {
+        let colors = [hex("#1d2021"), hex("#cc241d"), hex("#98971a")];
+        assert_eq!(palette(&colors, &TuningConfig::default()), colors);
+    }
+
+    #[test]
+    fn clamps_lightness_to_the_brightness_range() {
+        let tuning = tuning(|t| t.brightness_range = [0.3, 0.8]);
+        for color in palette(&[hex("#000000"), hex("#ffffff"), hex("#808080")], &tuning) {
+            let l = Oklab::from(color).l;
+            assert!((0.29..=0.81).contains(&l), "{color:?} has lightness {l}");
+        }
+    }
+
+    #[test]
+    fn min_chroma_tints_only_colored_entries() {
+        let tuning = tuning(|t| t.min_chroma = 0.15);
+        let tuned = palette(&[hex("#806050"), hex("#808080")], &tuning);
+        assert!(Oklab::from(tuned[0]).chroma() > 0.12, "{:?}", tuned[0]);
+        assert_eq!(tuned[1], hex("#808080"));
+    }
+
+    #[test]
+    fn negative_saturation_boosts_desaturate_instead_of_inverting() {
+        let tuning = tuning(|t| t.saturation_boost = -1.0);
+        let gray = palette(&[hex("#cc241d")], &tuning)[0];
+        assert!(Oklab::from(gray).chroma() < 0.01, "{gray:?}");
+    }
+
+    #[test]
+    fn shifts_the_accent_hue_only() {
+        let mut roles = Roles {
+            bg: hex("#1d2021"),
+            fg: hex("#ebdbb2"),
+            accent: hex("#458588"),
+            warn: hex("#d79921"),
+            ok: hex("#98971a"),
+        };
+        let before = roles;
+
+        accent(&mut roles, &TuningConfig::default());
+        assert_eq!(roles, before);
+
+        accent(&mut roles, &tuning(|t| t.accent_hue_shift = 180.0));
+        assert_ne!(roles.accent, before.accent);
+        assert_eq!(roles.bg, before.bg);
+        let (old, new) = (Oklab::from(before.accent), Oklab::from(roles.accent));
+        assert!((old.l - new.l).abs() < 0.01);
+        assert!(new.a.signum() != old.a.signum() || new.b.signum() != old.b.signum());
+    }
+}
@@ -1,4 +1,7 @@
-use anyhow::Result;
+use crate::cli::Config;
+use crate::error::Result;
+use crate::rotation::Rotation;
+use crate::theme::Roles;
 use async_trait::async_trait;
 
 /// Defines the interface for interacting with different Window Managers or Compositors.
@@ -6,7 +9,7 @@ use async_trait::async_trait;
 /// This trait allows the core logic to remain agnostic of the underlying display protocol
 /// (e.g., Wayland vs. X11) or specific compositor implementations (e.g., Hyprland vs. Sway).
 #[async_trait]
-pub trait Backend {
+pub trait Backend: Send + Sync {
     /// Returns a list of unique identifiers for monitors that are currently powered on and active.
     ///
     /// These identifiers (e.g., "HDMI-A-1", "eDP-1") are typically used to target
@@ -14,7 +17,56 @@ pub trait Backend {
     ///
     /// # Errors
     ///
-    /// Returns an error if the backend fails to communicate with the compositor
-    /// or if the monitor list cannot be parsed.
+    /// Returns [`Error::Backend`](crate::Error::Backend) if the backend fails to communicate with the
+    /// compositor or if the monitor list cannot be parsed.
     async fn get_active_monitors(&self) -> Result<Vec<String>>;
+
+    /// Returns the identifier of the monitor that currently has focus, if the
+    /// compositor reports one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Backend`](crate::Error::Backend) if the backend fails to communicate with the compositor.
+    async fn get_focused_monitor(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Applies theme colors to the compositor's window borders.
+    ///
+    /// The accent role is used for focused windows and the background role for
+    /// unfocused ones. Backends that cannot style borders keep the default no-op.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Backend`](crate::Error::Backend) if the compositor rejects or cannot receive the commands.
+    async fn set_border_colors(&self, _roles: &Roles) -> Result<()> {
+        Ok(())
+    }
+
+    /// Resolves once the set of connected outputs changes, so the daemon can
+    /// cover a new monitor right away instead of at the next rotation.
+    ///
+    /// Backends without an event source keep the default, which never resolves.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Backend`](crate::Error::Backend) if the event source fails; the daemon then stops
+    /// listening and relies on its timer alone.
+    async fn wait_for_output_change(&self) -> Result<()> {
+        std::future::pending().await
+    }
+}
+
+/// Puts a rotation's wallpapers on screen.
+///
+/// The daemon keeps one renderer for its whole lifetime, so implementations can
+/// hold on to state between rotations (such as a running `swaybg` child).
+#[async_trait]
+pub trait Render: Send {
+    /// Applies every `(monitor, image)` assignment of `rotation`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Render`](crate::Error::Render) if the wallpaper tool cannot be run or rejects the images.
+    async fn apply(&mut self, config: &Config, rotation: &Rotation) -> Result<()>;
 }
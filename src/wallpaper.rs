@@ -1,3 +1,4 @@
+use crate::error::{Error, Result};
 use rand::prelude::IndexedRandom;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
@@ -19,12 +20,10 @@ impl WallpaperCache {
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - The directory cannot be read.
-    /// - No supported image files are found.
-    pub fn new<P: AsRef<Path>>(dir: P) -> anyhow::Result<Self> {
-        // Ensure the theme directory/fallback CSS exists before we start picking wallpapers
-        crate::theme::ensure_theme_exists()?;
+    /// - [`Error::Scan`] if the directory cannot be read.
+    /// - [`Error::NoWallpapers`] if no supported image files are found.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
         let mut files = Vec::new();
 
         // Recursively walk through the directory
@@ -61,7 +60,7 @@ impl WallpaperCache {
         }
 
         if files.is_empty() {
-            anyhow::bail!("No supported images found in directory.");
+            return Err(Error::NoWallpapers(dir.to_path_buf()));
         }
 
         log::info!("Cached {} wallpapers.", files.len());
@@ -74,6 +73,7 @@ impl WallpaperCache {
     ///
     /// Panics if the cache is empty, though the `new` constructor
     /// guarantees at least one file is present.
+    #[must_use]
     pub fn pick_random(&self) -> &Path {
         let mut rng = rand::rng();
         self.files
@@ -0,0 +1,283 @@
+//! Runs the `randpaper` binary against fake compositors and wallpaper tools.
+//!
+//! Each test gets its own home, runtime dir and a `PATH` holding nothing but
+//! stub scripts, so no real compositor, renderer or theme reload is touched.
+#![cfg(feature = "extract")]
+mod common;
+
+use common::StubDir;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixListener;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+use std::time::{Duration, Instant};
+
+const SWAY_OUTPUTS: &str = r#"[
+  {"name": "DP-1", "active": true, "focused": true, "make": "Dell Inc.", "model": "DELL U2720Q", "serial": "5KC0S3"},
+  {"name": "HDMI-A-1", "active": true, "focused": false, "make": "Goldstar Company Ltd", "model": "LG TV", "serial": "0x01010101"},
+  {"name": "eDP-1", "active": false, "focused": false}
+]"#;
+
+const HYPR_MONITORS: &str = r#"[
+  {"id": 0, "name": "DP-1", "make": "Dell Inc.", "model": "DELL U2720Q", "serial": "5KC0S3", "focused": true},
+  {"id": 1, "name": "HDMI-A-1", "make": "Goldstar Company Ltd", "model": "LG TV", "serial": "0x01010101", "focused": false}
+]"#;
+
+/// A throwaway session: home, runtime dir, wallpapers and stub tools.
+struct Session {
+    root: StubDir,
+    /// The only directory on `PATH`.
+    bin: StubDir,
+}
+
+impl Session {
+    fn new() -> Self {
+        let root = StubDir::new();
+        for dir in ["home", "run", "walls"] {
+            std::fs::create_dir_all(root.path(dir)).expect("create session dir");
+        }
+        let session = Self {
+            root,
+            bin: StubDir::new(),
+        };
+        // The swaybg renderer clears old instances first
+        session.stub("pkill", "", 0);
+        session
+    }
+
+    fn path(&self, rel: &str) -> PathBuf {
+        self.root.path(rel)
+    }
+
+    fn wallpaper(&self, name: &str, rgb: [u8; 3]) -> PathBuf {
+        let path = self.path("walls").join(name);
+        image::RgbImage::from_pixel(8, 8, image::Rgb(rgb))
+            .save(&path)
+            .expect("write wallpaper");
+        path.canonicalize().expect("wallpaper path")
+    }
+
+    /// Puts `name` on `PATH`: it logs its arguments, prints `stdout` and exits with `status`.
+    fn stub(&self, name: &str, stdout: &str, status: i32) {
+        self.bin.command(name, stdout, status);
+    }
+
+    /// The recorded invocations of stub `name`, each as its argument list.
+    fn calls(&self, name: &str) -> Vec<Vec<String>> {
+        self.bin.calls(name)
+    }
+
+    /// Polls until stub `name` has been called at least `count` times.
+    fn wait_for_calls(&self, name: &str, count: usize) -> Vec<Vec<String>> {
+        let deadline = Instant::now() + Duration::from_secs(15);
+        loop {
+            let calls = self.calls(name);
+            if calls.len() >= count {
+                return calls;
+            }
+            assert!(
+                Instant::now() < deadline,
+                "{name} called {} time(s), expected {count}",
+                calls.len()
+            );
+            std::thread::sleep(Duration::from_millis(25));
+        }
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_randpaper"));
+        cmd.env_clear()
+            .env("PATH", self.bin.path(""))
+            .env("HOME", self.path("home"))
+            .env("XDG_CONFIG_HOME", self.path("home/.config"))
+            .env("XDG_CACHE_HOME", self.path("home/.cache"))
+            .env("XDG_RUNTIME_DIR", self.path("run"))
+            .arg("--wallpaper-dir")
+            .arg(self.path("walls"))
+            .args(args);
+        cmd
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        self.command(args).output().expect("run randpaper")
+    }
+
+    fn spawn(&self, args: &[&str]) -> Child {
+        self.command(args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("spawn randpaper")
+    }
+
+    /// Serves `j/monitors` on a fake Hyprland request socket for every connection.
+    fn serve_hyprland(&self, signature: &str) {
+        let dir = self.path("run/hypr").join(signature);
+        std::fs::create_dir_all(&dir).expect("create hypr dir");
+        let listener = UnixListener::bind(dir.join(".socket.sock")).expect("bind hypr socket");
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut buf = [0; 1024];
+                let n = stream.read(&mut buf).unwrap_or(0);
+                let reply = if &buf[..n] == b"j/monitors" {
+                    HYPR_MONITORS
+                } else {
+                    "ok"
+                };
+                let _ = stream.write_all(reply.as_bytes());
+            }
+        });
+    }
+}
+
+fn assert_success(out: &Output) {
+    assert!(
+        out.status.success(),
+        "randpaper failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+}
+
+fn display(path: &Path) -> String {
+    path.display().to_string()
+}
+
+#[test]
+fn oneshot_sets_swaybg_on_every_active_sway_output() {
+    let session = Session::new();
+    let wall = session.wallpaper("red.png", [200, 40, 40]);
+    session.stub("swaymsg", SWAY_OUTPUTS, 0);
+    session.stub("swaybg", "", 0);
+
+    assert_success(&session.run(&["--backend", "sway", "--renderer", "swaybg"]));
+
+    assert_eq!(session.calls("swaymsg")[0], ["-t", "get_outputs", "-r"]);
+    assert!(
+        session
+            .calls("pkill")
+            .contains(&vec!["-x".to_string(), "swaybg".to_string()])
+    );
+    let wall = display(&wall);
+    assert_eq!(
+        session.wait_for_calls("swaybg", 1)[0],
+        [
+            "-o", "DP-1", "-m", "fill", "-i", &wall, //
+            "-o", "HDMI-A-1", "-m", "fill", "-i", &wall,
+        ]
+    );
+}
+
+#[test]
+fn oneshot_sends_awww_transitions_per_output() {
+    let session = Session::new();
+    let wall = display(&session.wallpaper("red.png", [200, 40, 40]));
+    session.stub("swaymsg", SWAY_OUTPUTS, 0);
+    session.stub("awww", "", 0);
+
+    assert_success(&session.run(&[
+        "--backend",
+        "sway",
+        "--renderer",
+        "awww",
+        "--transition-type",
+        "wipe",
+        "--transition-step",
+        "45",
+        "--transition-fps",
+        "60",
+    ]));
+
+    let img = |output: &str| {
+        [
+            "img",
+            &wall,
+            "-o",
+            output,
+            "--transition-type",
+            "wipe",
+            "--transition-step",
+            "45",
+            "--transition-fps",
+            "60",
+            "--resize",
+            "crop",
+        ]
+        .map(String::from)
+        .to_vec()
+    };
+    let calls = session.calls("awww");
+    assert_eq!(calls[..2], [vec!["--help"], vec!["query"]]);
+    assert_eq!(calls[2..], [img("DP-1"), img("HDMI-A-1")]);
+}
+
+#[test]
+fn oneshot_applies_output_filters_on_hyprland() {
+    let session = Session::new();
+    let wall = display(&session.wallpaper("red.png", [200, 40, 40]));
+    session.serve_hyprland("test_sig");
+    session.stub("swaybg", "", 0);
+
+    let out = session
+        .command(&[
+            "--backend",
+            "hyprland",
+            "--renderer",
+            "swaybg",
+            "--outputs=!*LG TV*",
+        ])
+        .env("HYPRLAND_INSTANCE_SIGNATURE", "test_sig")
+        .output()
+        .expect("run randpaper");
+    assert_success(&out);
+
+    assert_eq!(
+        session.wait_for_calls("swaybg", 1)[0],
+        ["-o", "DP-1", "-m", "fill", "-i", &wall]
+    );
+}
+
+#[test]
+fn oneshot_reports_unreachable_compositors() {
+    let session = Session::new();
+    session.wallpaper("red.png", [200, 40, 40]);
+    session.stub("swaymsg", "", 1);
+    session.stub("swaybg", "", 0);
+
+    let out = session.run(&["--backend", "sway", "--renderer", "swaybg"]);
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("swaymsg get_outputs failed"), "{stderr}");
+    assert!(session.calls("swaybg").is_empty());
+}
+
+#[test]
+fn daemon_rotates_on_sigusr1() {
+    let session = Session::new();
+    session.wallpaper("red.png", [200, 40, 40]);
+    session.wallpaper("blue.png", [30, 60, 200]);
+    session.stub("swaymsg", SWAY_OUTPUTS, 0);
+    session.stub("swaybg", "", 0);
+
+    let mut daemon = session.spawn(&[
+        "--daemon",
+        "--time",
+        "1h",
+        "--backend",
+        "sway",
+        "--renderer",
+        "swaybg",
+    ]);
+    session.wait_for_calls("swaybg", 1);
+
+    let signalled = Command::new("kill")
+        .args(["-USR1", &daemon.id().to_string()])
+        .status()
+        .expect("run kill");
+    assert!(signalled.success());
+    let calls = session.wait_for_calls("swaybg", 2);
+
+    let _ = daemon.kill();
+    let _ = daemon.wait();
+    assert_eq!(calls[1].iter().filter(|a| *a == "-o").count(), 2);
+}
@@ -0,0 +1,67 @@
+//! Scratch directories and stand-in command-line tools for the CLI tests.
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A temporary directory, removed on drop, that can hold fake executables
+/// logging their arguments.
+pub struct StubDir {
+    dir: PathBuf,
+}
+
+impl StubDir {
+    pub fn new() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "randpaper-it-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).expect("create stub dir");
+        Self { dir }
+    }
+
+    /// Writes an executable `name` that appends one line per argument (then a
+    /// `--` separator) to `name.log`, prints `stdout` and exits with `status`.
+    pub fn command(&self, name: &str, stdout: &str, status: i32) {
+        let path = self.dir.join(name);
+        let script = format!(
+            "#!/bin/sh\nfor arg in \"$@\"; do printf '%s\\n' \"$arg\" >> '{log}'; done\necho -- >> '{log}'\nprintf '%s' '{stdout}'\nexit {status}\n",
+            log = self.log_path(name).display(),
+            stdout = stdout.replace('\'', r"'\''"),
+        );
+        std::fs::write(&path, script).expect("write stub");
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+            .expect("chmod stub");
+    }
+
+    /// The invocations recorded for `name`, each as its list of arguments.
+    pub fn calls(&self, name: &str) -> Vec<Vec<String>> {
+        let log = std::fs::read_to_string(self.log_path(name)).unwrap_or_default();
+        let mut calls = Vec::new();
+        let mut current = Vec::new();
+        for line in log.lines() {
+            if line == "--" {
+                calls.push(std::mem::take(&mut current));
+            } else {
+                current.push(line.to_string());
+            }
+        }
+        calls
+    }
+
+    /// A path inside the directory.
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    fn log_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.log"))
+    }
+}
+
+impl Drop for StubDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}